@@ -1,20 +1,32 @@
-use clap::Command;
+use clap::builder::FalseyValueParser;
+use clap::error::ErrorKind;
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use futures::stream::{self, StreamExt};
 use anyhow::{anyhow, Context, Result};
 use std::ffi::OsStr;
+use std::io::IsTerminal;
 use std::process::exit;
 
 use crate::parser::config::{CliConfig, ComposeItem, DefaultCommandArgs};
-use crate::utils::docker::Container;
+use crate::utils::docker::{CommandOutput, Container};
+use crate::utils::dry_run::{format_command_line, get_dry_run_format, is_dry_run, DryRunFormat};
+use crate::utils::engine::get_project_container;
+use crate::utils::secret::{mask_env, resolve_env};
+use crate::utils::error::DctlError;
 
+use crate::command::args::{has_project_selector, ProjectTarget};
 use crate::command::cd::{cd_project, exec_cd_project};
 use crate::command::completion::{exec_shell_completion, shell_completion};
 use crate::command::config::{check_config, exec_check_config};
 use crate::command::infos::{exec_projects_infos, projects_infos};
+use crate::command::migrate::{exec_migrate_config, migrate_config};
 use crate::command::register::{exec_register_project, register_project};
+use crate::command::schema::{exec_schema_config, schema_config};
+use crate::command::show::{exec_show_config, show_config};
 use crate::command::registry::{get_compose_commands, get_command_by_name};
 use crate::command::unregister::{exec_unregister_project, unregister_project};
 
-fn cli() -> Command {
+pub fn cli() -> Command {
     let mut cmd = Command::new("dctl")
         .about("A docker-compose missing feature.")
         .long_about(
@@ -23,11 +35,64 @@ fn cli() -> Command {
         .version(version!())
         .author("Fabien D. <fabien@myprod.net>")
         .subcommand_required(true)
-        .arg_required_else_help(true);
+        .arg_required_else_help(true)
+        .arg(
+            Arg::new("JOBS")
+                .long("jobs")
+                .help("Maximum number of projects to run in parallel")
+                .value_parser(clap::value_parser!(u64).range(1..))
+                .global(true),
+        )
+        .arg(
+            Arg::new("NO_DEFAULTS")
+                .long("no-defaults")
+                .help("Don't use the default command args of the configuration")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("PROFILE")
+                .long("profile")
+                .help("Enable a compose profile, in addition to the profiles of the projects")
+                .action(ArgAction::Append)
+                .global(true),
+        )
+        .arg(
+            Arg::new("NO_PROFILES")
+                .long("no-profiles")
+                .help("Don't use the profiles of the configuration, only the --profile ones")
+                .action(ArgAction::SetTrue)
+                .global(true),
+        )
+        .arg(
+            Arg::new("DRY_RUN")
+                .long("dry-run")
+                .help("Print the docker commands instead of executing them")
+                .env("DCTL_DRY_RUN")
+                .action(ArgAction::SetTrue)
+                .value_parser(FalseyValueParser::new())
+                .global(true),
+        )
+        .arg(
+            Arg::new("DRY_RUN_FORMAT")
+                .long("dry-run-format")
+                .help("Output format of the dry run")
+                .value_parser(["shell", "json"])
+                .default_value("shell")
+                .global(true),
+        );
 
-    // Add all docker compose commands from registry
+    // Add all docker compose commands from registry, `config` also manages the dctl config
     for handler in get_compose_commands() {
-        cmd = cmd.subcommand(handler.cli());
+        let mut subcommand = handler.cli();
+        if handler.name() == "config" {
+            subcommand = subcommand
+                .args_conflicts_with_subcommands(true)
+                .subcommand(show_config())
+                .subcommand(migrate_config())
+                .subcommand(schema_config());
+        }
+        cmd = cmd.subcommand(subcommand);
     }
 
     // Add other commands
@@ -42,16 +107,31 @@ fn cli() -> Command {
     cmd
 }
 
-pub async fn run(container: &dyn Container, config: &mut dyn CliConfig) -> Result<()> {
+/// Run the commands that don't use the config, before it is loaded: `dctl config migrate`
+/// fixes a config file that may not load, and `dctl config schema` describes any file
+pub fn run_without_config(matches: &ArgMatches) -> Option<Result<()>> {
+    let ("config", args) = matches.subcommand()? else {
+        return None;
+    };
+    match args.subcommand() {
+        Some(("migrate", args)) => Some(exec_migrate_config(args)),
+        Some(("schema", _)) => Some(exec_schema_config()),
+        _ => None,
+    }
+}
+
+pub async fn run(
+    container: &dyn Container,
+    config: &mut dyn CliConfig,
+    matches: &ArgMatches,
+) -> Result<()> {
     // Get the command name and args
-    let matches = cli().get_matches();
     let (command_name, args) = matches.subcommand().context("No subcommand provided")?;
-    let default_command_args = config.get_default_command_args(command_name);
 
     // Handle special commands that don't need a project
     match command_name {
         "infos" => {
-            exec_projects_infos(config, container).await?;
+            exec_projects_infos(config, container, args).await?;
             return Ok(());
         }
         "check-config" => {
@@ -73,35 +153,363 @@ pub async fn run(container: &dyn Container, config: &mut dyn CliConfig) -> Resul
         _ => {}
     }
 
+    // Handle the dctl config commands
+    if command_name == "config" {
+        if let Some(("show", args)) = args.subcommand() {
+            return exec_show_config(config, args);
+        }
+    }
+
     // For next commands, we need a project
     if args.try_contains_id("PROJECT").is_err() {
         exit(1)
     }
 
-    // Get the compose item for the project
-    let compose_item = match args.get_one::<String>("PROJECT") {
-        Some(name) => match config.get_compose_item_by_alias(name.to_string()) {
-            Some(item) => item,
-            None => return Err(anyhow!("No project found with alias: {}", name)),
-        },
-        None => exit(1),
-    };
-
     // Handle cd command
     if command_name == "cd" {
+        let compose_item = match args.get_one::<String>("PROJECT") {
+            Some(name) => config.resolve_compose_item(name)?,
+            None => exit(1),
+        };
         let _result = exec_cd_project(&compose_item);
         exit(0);
     }
 
-    // Build configuration args
-    let mut config_args: Vec<&OsStr> = vec![];
-    config_args.append(&mut ComposeItem::to_args(&compose_item));
+    // Get main default command args, projects can override them
+    let command_args = config.get_default_command_args(command_name);
+
+    // Execute docker compose command on every targeted project, in a project directory
+    // a PROJECT value that is not an alias is the first arg of the command
+    let cwd_item = match is_project_value_an_arg(config, args) {
+        true => get_cwd_compose_item(config),
+        false => None,
+    };
+    let (mut compose_items, target) = match cwd_item {
+        Some(item) => (vec![item], ProjectTarget::CurrentDir),
+        None => {
+            let mut compose_items = get_targeted_compose_items(config, args)?;
+            let service_items = get_service_compose_items(config, args);
+            let target = ProjectTarget::Aliases(service_items.len());
+            for item in service_items {
+                if !compose_items.iter().any(|i| i.alias == item.alias) {
+                    compose_items.push(item);
+                }
+            }
+            (compose_items, target)
+        }
+    };
+    set_cli_profiles(&mut compose_items, args);
+    check_required_args(command_name, args, target);
+
+    // Print the command lines instead of executing them
+    if is_dry_run(args) {
+        let format = get_dry_run_format(args);
+        for compose_item in &compose_items {
+            print_compose_command(
+                container,
+                command_name,
+                compose_item,
+                &command_args,
+                args,
+                target,
+                format,
+            )?;
+        }
+        return Ok(());
+    }
+
+    if compose_items.len() == 1 {
+        let config_env = get_project_env(container, &compose_items[0]).await?;
+        return exec_compose_command(
+            container,
+            command_name,
+            &compose_items[0],
+            &config_env,
+            &command_args,
+            args,
+            target,
+            None,
+        )
+        .await;
+    }
+
+    // Read the secrets of the projects one at a time, their commands may prompt
+    let mut configs_env = Vec::new();
+    for compose_item in &compose_items {
+        configs_env.push(get_project_env(container, compose_item).await);
+    }
+
+    // Run projects in parallel, their output is streamed with a prefix
+    let jobs = match args.get_one::<u64>("JOBS") {
+        Some(jobs) => *jobs as usize,
+        None => compose_items.len(),
+    }
+    .max(1);
+    let prefixes = get_projects_prefixes(&compose_items, use_colors());
+    let projects = compose_items.iter().zip(prefixes).zip(configs_env);
+    let results: Vec<(String, Result<()>)> = stream::iter(projects)
+        .map(|((compose_item, prefix), config_env)| {
+            let command_args = &command_args;
+            async move {
+                let result = match config_env {
+                    Ok(config_env) => {
+                        exec_compose_command(
+                            container,
+                            command_name,
+                            compose_item,
+                            &config_env,
+                            command_args,
+                            args,
+                            target,
+                            Some(CommandOutput::Stream(prefix)),
+                        )
+                        .await
+                    }
+                    Err(err) => Err(err),
+                };
+                (compose_item.alias.to_string(), result)
+            }
+        })
+        .buffered(jobs)
+        .collect()
+        .await;
+
+    print_projects_summary(&results)
+}
+
+/// Colors used to tell projects apart in a streamed output
+const PREFIX_COLORS: [&str; 6] = ["36", "33", "32", "35", "34", "31"];
+
+fn use_colors() -> bool {
+    std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+}
+
+/// Build the `[alias]` tag of each project, aligned on the longest alias
+fn get_projects_prefixes(compose_items: &[ComposeItem], colored: bool) -> Vec<String> {
+    let width = compose_items
+        .iter()
+        .map(|item| item.alias.len() + 2)
+        .max()
+        .unwrap_or(0);
+
+    compose_items
+        .iter()
+        .enumerate()
+        .map(|(index, item)| {
+            let tag = format!("{:width$}", format!("[{}]", item.alias), width = width);
+            if colored {
+                let color = PREFIX_COLORS[index % PREFIX_COLORS.len()];
+                format!("\x1b[{}m{}\x1b[0m", color, tag)
+            } else {
+                tag
+            }
+        })
+        .collect()
+}
+
+/// Project of the current directory, if any
+fn get_cwd_compose_item(config: &dyn CliConfig) -> Option<ComposeItem> {
+    let current_dir = std::env::current_dir().ok()?;
+    config.get_compose_item_by_dir(&current_dir)
+}
+
+/// Whether the PROJECT value is not an exact alias nor a group, in a project directory
+/// it's then the first arg of the command run on this project
+fn is_project_value_an_arg(config: &dyn CliConfig, args: &ArgMatches) -> bool {
+    if has_project_selector(args) {
+        return false;
+    }
+    let is_project = |name: &String| {
+        name.starts_with('@') || config.get_compose_item_by_alias(name.to_string()).is_some()
+    };
+
+    match args.get_many::<String>("PROJECT") {
+        Some(mut names) => !names.all(is_project),
+        None => false,
+    }
+}
+
+/// Projects named by the first services after the PROJECT value, as in
+/// `dctl up web data tools`, stopping at the first one that is not an exact alias
+fn get_service_compose_items(config: &dyn CliConfig, args: &ArgMatches) -> Vec<ComposeItem> {
+    if has_project_selector(args) || args.get_many::<String>("PROJECT").is_none() {
+        return Vec::new();
+    }
+    match args.try_get_many::<String>("SERVICE") {
+        Ok(Some(services)) => services
+            .map_while(|service| config.get_compose_item_by_alias(service.to_string()))
+            .collect(),
+        _ => Vec::new(),
+    }
+}
 
-    // Get default command args
-    let command_args = match default_command_args {
-        Some(command_args) => command_args,
-        None => DefaultCommandArgs::default(command_name),
+/// Exit with a usage error when a required positional arg of the command is missing,
+/// clap can't check them after the optional PROJECT
+fn check_required_args(command_name: &str, args: &ArgMatches, target: ProjectTarget) {
+    let Some(handler) = get_command_by_name(command_name) else {
+        return;
     };
+    let missing = handler.missing_required_args(args, target);
+    if missing.is_empty() {
+        return;
+    }
+
+    let mut cmd = cli();
+    cmd.build();
+    if let Some(subcommand) = cmd.find_subcommand_mut(command_name) {
+        let message = format!(
+            "the following required arguments were not provided:\n  {}",
+            missing.join("\n  ")
+        );
+        subcommand.error(ErrorKind::MissingRequiredArgument, message).exit();
+    }
+}
+
+/// Add the profiles given with `--profile` to the ones of each project, or replace
+/// them with `--no-profiles`
+fn set_cli_profiles(compose_items: &mut [ComposeItem], args: &ArgMatches) {
+    let cli_profiles: Vec<String> = match args.try_get_many::<String>("PROFILE").ok().flatten() {
+        Some(values) => values.cloned().collect(),
+        None => Vec::new(),
+    };
+    let no_profiles = args.try_get_one::<bool>("NO_PROFILES").ok().flatten() == Some(&true);
+
+    for item in compose_items {
+        let mut profiles = match no_profiles {
+            true => Vec::new(),
+            false => item.profiles.take().unwrap_or_default(),
+        };
+        for profile in &cli_profiles {
+            if !profiles.contains(profile) {
+                profiles.push(profile.to_string());
+            }
+        }
+        item.profiles = (!profiles.is_empty()).then_some(profiles);
+    }
+}
+
+/// Resolve the projects targeted by a command: one or several aliases, every
+/// project of a group given as `@group` or with the `--group` option, every
+/// registered project with `--all`, or the project of the current directory.
+fn get_targeted_compose_items(
+    config: &dyn CliConfig,
+    args: &ArgMatches,
+) -> Result<Vec<ComposeItem>> {
+    if args.try_get_one::<bool>("ALL_PROJECTS").ok().flatten() == Some(&true) {
+        let compose_items = config.get_all_compose_items();
+        if compose_items.is_empty() {
+            return Err(DctlError::NoProject.into());
+        }
+        return Ok(compose_items);
+    }
+
+    let names: Vec<String> = match args.try_get_one::<String>("GROUP").ok().flatten() {
+        Some(group) => vec![format!("@{}", group)],
+        None => match args.get_many::<String>("PROJECT") {
+            Some(values) => values.cloned().collect(),
+            None => match get_cwd_compose_item(config) {
+                Some(item) => return Ok(vec![item]),
+                None => return Err(DctlError::NoProject.into()),
+            },
+        },
+    };
+
+    let mut compose_items: Vec<ComposeItem> = Vec::new();
+    for name in names {
+        let found = match name.strip_prefix('@') {
+            Some(group) => {
+                let items = config.get_compose_items_by_group(group.to_string());
+                if items.is_empty() {
+                    return Err(DctlError::UnknownGroup(group.to_string()).into());
+                }
+                items
+            }
+            None => vec![config.resolve_compose_item(&name)?],
+        };
+
+        // A project can be targeted several times, run it once
+        for item in found {
+            if !compose_items.iter().any(|i| i.alias == item.alias) {
+                compose_items.push(item);
+            }
+        }
+    }
+
+    Ok(compose_items)
+}
+
+/// Print the result of a command run on several projects, fails if one of them failed
+fn print_projects_summary(results: &[(String, Result<()>)]) -> Result<()> {
+    eprintln!("\nSummary :\n");
+    for (alias, result) in results {
+        match result {
+            Ok(_) => eprintln!("✅ - {}", alias),
+            Err(err) => eprintln!("❌ - {}: {}", alias, err),
+        }
+    }
+
+    let failed = results.iter().filter(|(_, result)| result.is_err()).count();
+    if failed == 0 {
+        return Ok(());
+    }
+
+    // Exit with the code of the first failed docker command, if any
+    let command_error = results
+        .iter()
+        .filter_map(|(_, result)| result.as_ref().err())
+        .filter_map(|err| err.downcast_ref::<DctlError>())
+        .find(|err| matches!(err, DctlError::CommandFailed(_) | DctlError::Aborted { .. }));
+    match command_error {
+        Some(command_error) => Err(command_error.clone().into()),
+        None => Err(anyhow!("{} of {} projects failed", failed, results.len())),
+    }
+}
+
+/// Default command args of a project, unless disabled with --no-defaults
+fn get_project_command_args(
+    command_name: &str,
+    compose_item: &ComposeItem,
+    main_command_args: &Option<DefaultCommandArgs>,
+    args: &ArgMatches,
+) -> DefaultCommandArgs {
+    let no_defaults = args.try_get_one::<bool>("NO_DEFAULTS").ok().flatten() == Some(&true);
+
+    match compose_item.get_default_command_args(command_name, main_command_args.clone()) {
+        Some(command_args) if !no_defaults => command_args,
+        _ => DefaultCommandArgs::default(command_name),
+    }
+}
+
+/// Env of a project for the engine running it, reading its secrets
+async fn get_project_env(
+    container: &dyn Container,
+    compose_item: &ComposeItem,
+) -> Result<Vec<(String, String)>> {
+    let project_container = get_project_container(container, compose_item);
+    let container = project_container.as_deref().unwrap_or(container);
+
+    resolve_env(&compose_item.alias, &compose_item.to_env(container.engine())).await
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn exec_compose_command(
+    container: &dyn Container,
+    command_name: &str,
+    compose_item: &ComposeItem,
+    config_env: &[(String, String)],
+    main_command_args: &Option<DefaultCommandArgs>,
+    args: &ArgMatches,
+    target: ProjectTarget,
+    command_output: Option<CommandOutput>,
+) -> Result<()> {
+    // Build configuration args
+    let config_args: Vec<&OsStr> = ComposeItem::to_args(compose_item);
+    let project_container = get_project_container(container, compose_item);
+    let container = project_container.as_deref().unwrap_or(container);
+
+    // Get default command args of the project
+    let command_args =
+        get_project_command_args(command_name, compose_item, main_command_args, args);
     let default_command_arg = DefaultCommandArgs::to_args(&command_args);
 
     // Execute docker compose command using registry
@@ -110,9 +518,11 @@ pub async fn run(container: &dyn Container, config: &mut dyn CliConfig) -> Resul
             .compose(
                 handler.command_type(),
                 &config_args,
+                config_env,
                 &default_command_arg,
                 args,
-                None,
+                target,
+                command_output,
             )
             .await?;
     } else {
@@ -122,12 +532,263 @@ pub async fn run(container: &dyn Container, config: &mut dyn CliConfig) -> Resul
     Ok(())
 }
 
+/// Print the command line of a docker compose command instead of executing it
+fn print_compose_command(
+    container: &dyn Container,
+    command_name: &str,
+    compose_item: &ComposeItem,
+    main_command_args: &Option<DefaultCommandArgs>,
+    args: &ArgMatches,
+    target: ProjectTarget,
+    format: DryRunFormat,
+) -> Result<()> {
+    let handler = get_command_by_name(command_name)
+        .ok_or_else(|| anyhow!("Unknown command: {}", command_name))?;
+
+    let config_args: Vec<&OsStr> = ComposeItem::to_args(compose_item);
+    let command_args =
+        get_project_command_args(command_name, compose_item, main_command_args, args);
+    let default_command_arg = DefaultCommandArgs::to_args(&command_args);
+
+    let project_container = get_project_container(container, compose_item);
+    let container = project_container.as_deref().unwrap_or(container);
+    let command_line = container.command_line(
+        handler.command_type(),
+        &config_args,
+        &default_command_arg,
+        args,
+        target,
+    )?;
+    // A secret is never printed, nor read
+    let config_env = mask_env(&compose_item.to_env(container.engine()));
+    println!("{}", format_command_line(&compose_item.alias, &config_env, &command_line, format));
+
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::command::definitions::{logs_def, up_def};
+    use crate::parser::config::DctlConfig;
+    use crate::utils::system::CommandError;
+
+    fn get_config() -> DctlConfig {
+        let config = r#"
+        [main]
+        docker_bin = "docker"
+
+        [[collections]]
+        alias = "web"
+        groups = ["front"]
+        compose_files = ["/home/test/web/docker-compose.yml"]
+        profiles = ["debug"]
+
+        [[collections]]
+        alias = "data"
+        groups = ["front", "back"]
+        compose_files = ["/home/test/data/docker-compose.yml"]
+
+        [[collections]]
+        alias = "tools"
+        compose_files = ["/home/test/tools/docker-compose.yml"]
+        "#;
+
+        toml::from_str(config).unwrap()
+    }
+
+    fn get_aliases(items: Vec<ComposeItem>) -> Vec<String> {
+        items.into_iter().map(|item| item.alias).collect()
+    }
 
     #[test]
     fn it_verifies_the_cli() {
         cli().debug_assert();
     }
+
+    #[test]
+    fn it_targets_a_single_project() {
+        let matches = up_def().to_clap_command().get_matches_from(vec!["up", "tools"]);
+        let items = get_targeted_compose_items(&get_config(), &matches).unwrap();
+        assert_eq!(get_aliases(items), vec!["tools"]);
+    }
+
+    #[test]
+    fn it_targets_a_group_with_the_at_prefix() {
+        let matches = up_def().to_clap_command().get_matches_from(vec!["up", "@front"]);
+        let items = get_targeted_compose_items(&get_config(), &matches).unwrap();
+        assert_eq!(get_aliases(items), vec!["web", "data"]);
+    }
+
+    #[test]
+    fn it_targets_a_group_with_the_group_option() {
+        let matches = up_def()
+            .to_clap_command()
+            .get_matches_from(vec!["up", "--group", "back"]);
+        let items = get_targeted_compose_items(&get_config(), &matches).unwrap();
+        assert_eq!(get_aliases(items), vec!["data"]);
+    }
+
+    #[test]
+    fn it_targets_several_projects() {
+        let matches = up_def()
+            .to_clap_command()
+            .get_matches_from(vec!["up", "tools,@front,web"]);
+        let items = get_targeted_compose_items(&get_config(), &matches).unwrap();
+        assert_eq!(get_aliases(items), vec!["tools", "web", "data"]);
+    }
+
+    #[test]
+    fn it_targets_all_projects() {
+        let matches = up_def().to_clap_command().get_matches_from(vec!["up", "--all"]);
+        let items = get_targeted_compose_items(&get_config(), &matches).unwrap();
+        assert_eq!(get_aliases(items), vec!["web", "data", "tools"]);
+    }
+
+    #[test]
+    fn it_fails_when_no_project_is_registered() {
+        let config: DctlConfig =
+            toml::from_str("collections = []\n[main]\ndocker_bin = \"docker\"\n").unwrap();
+        let matches = up_def().to_clap_command().get_matches_from(vec!["up", "--all"]);
+        let error = get_targeted_compose_items(&config, &matches).unwrap_err();
+        assert_eq!(error.downcast_ref::<DctlError>().unwrap().exit_code(), 64);
+    }
+
+    fn get_profiles(argv: Vec<&str>) -> Vec<Option<Vec<String>>> {
+        let matches = cli().get_matches_from(argv);
+        let (_, args) = matches.subcommand().unwrap();
+        let mut items = get_targeted_compose_items(&get_config(), args).unwrap();
+        set_cli_profiles(&mut items, args);
+
+        items.into_iter().map(|item| item.profiles).collect()
+    }
+
+    #[test]
+    fn it_adds_the_profiles_of_the_command_line() {
+        let profiles = |values: &[&str]| Some(values.iter().map(|v| v.to_string()).collect());
+
+        assert_eq!(get_profiles(vec!["dctl", "up", "@front"]), vec![profiles(&["debug"]), None]);
+        assert_eq!(
+            get_profiles(vec!["dctl", "up", "--profile", "tools", "--profile", "debug", "@front"]),
+            vec![profiles(&["debug", "tools"]), profiles(&["tools", "debug"])]
+        );
+        assert_eq!(
+            get_profiles(vec!["dctl", "--no-profiles", "up", "--profile", "tools", "web"]),
+            vec![profiles(&["tools"])]
+        );
+        assert_eq!(get_profiles(vec!["dctl", "up", "--no-profiles", "web"]), vec![None]);
+    }
+
+    #[test]
+    fn it_builds_aligned_projects_prefixes() {
+        let items = get_config().get_all_compose_items();
+
+        let prefixes = get_projects_prefixes(&items, false);
+        assert_eq!(prefixes, vec!["[web]  ", "[data] ", "[tools]"]);
+
+        let prefixes = get_projects_prefixes(&items, true);
+        assert_eq!(prefixes[0], "\x1b[36m[web]  \x1b[0m");
+        assert_eq!(prefixes[1], "\x1b[33m[data] \x1b[0m");
+    }
+
+    #[test]
+    fn it_summarizes_the_projects_results() {
+        let results = vec![(String::from("web"), Ok(())), (String::from("data"), Ok(()))];
+        assert!(print_projects_summary(&results).is_ok());
+
+        let results = vec![
+            (String::from("web"), Ok(())),
+            (String::from("data"), Err(anyhow!("failed"))),
+        ];
+        let result = print_projects_summary(&results);
+        assert_eq!(result.unwrap_err().to_string(), "1 of 2 projects failed");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_returns_the_first_command_error_of_the_projects() {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::ExitStatus;
+
+        let results = vec![
+            (String::from("web"), Err(anyhow!("failed"))),
+            (
+                String::from("data"),
+                Err(DctlError::from(CommandError::new(ExitStatus::from_raw(3 << 8), vec![])).into()),
+            ),
+        ];
+        let error = print_projects_summary(&results).unwrap_err();
+        assert_eq!(error.downcast_ref::<DctlError>().unwrap().exit_code(), 3);
+    }
+
+    #[test]
+    fn it_takes_only_an_exact_alias_as_project_in_a_project_directory() {
+        let config: DctlConfig = toml::from_str(
+            r#"
+            [main]
+            docker_bin = "docker"
+
+            [[collections]]
+            alias = "shop"
+            compose_files = ["/home/test/shop/docker-compose.yml"]
+
+            [[collections]]
+            alias = "phpmyadmin"
+            groups = ["tools"]
+            compose_files = ["/home/test/phpmyadmin/docker-compose.yml"]
+            "#,
+        )
+        .unwrap();
+        let is_arg = |args: Vec<&str>| {
+            let matches = logs_def().to_clap_command().get_matches_from(args);
+            is_project_value_an_arg(&config, &matches)
+        };
+
+        // A prefix of an alias is a service of the current directory project
+        assert!(is_arg(vec!["logs", "-f", "php"]));
+        assert!(is_arg(vec!["logs", "shop,php"]));
+        assert!(!is_arg(vec!["logs", "-f", "phpmyadmin"]));
+        assert!(!is_arg(vec!["logs", "shop,phpmyadmin"]));
+        assert!(!is_arg(vec!["logs", "@tools"]));
+        assert!(!is_arg(vec!["logs", "--group", "tools", "php"]));
+        assert!(!is_arg(vec!["logs"]));
+
+        // Elsewhere, the prefix is resolved as usual
+        let matches = logs_def().to_clap_command().get_matches_from(vec!["logs", "-f", "php"]);
+        let items = get_targeted_compose_items(&config, &matches).unwrap();
+        assert_eq!(get_aliases(items), vec!["phpmyadmin"]);
+    }
+
+    #[test]
+    fn it_targets_the_projects_separated_by_spaces() {
+        let matches = up_def()
+            .to_clap_command()
+            .get_matches_from(vec!["up", "web", "data", "tools", "nginx", "web"]);
+        let items = get_service_compose_items(&get_config(), &matches);
+        assert_eq!(get_aliases(items), vec!["data", "tools"]);
+
+        let handler = get_command_by_name("up").unwrap();
+        let args = handler.prepare(&matches, ProjectTarget::Aliases(2));
+        assert_eq!(args, vec![OsStr::new("up"), OsStr::new("nginx"), OsStr::new("web")]);
+
+        // With a group, the services are services
+        let matches = up_def()
+            .to_clap_command()
+            .get_matches_from(vec!["up", "--group", "front", "tools"]);
+        assert!(get_service_compose_items(&get_config(), &matches).is_empty());
+    }
+
+    #[test]
+    fn it_fails_on_an_unknown_group() {
+        let matches = up_def().to_clap_command().get_matches_from(vec!["up", "@unknown"]);
+        let result = get_targeted_compose_items(&get_config(), &matches);
+        assert!(result.unwrap_err().to_string().contains("No project found in group"));
+    }
+
+    #[test]
+    fn it_fails_on_an_unknown_alias() {
+        let matches = up_def().to_clap_command().get_matches_from(vec!["up", "unknown"]);
+        let result = get_targeted_compose_items(&get_config(), &matches);
+        assert!(result.unwrap_err().to_string().contains("No project found with alias"));
+    }
 }
\ No newline at end of file
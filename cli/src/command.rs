@@ -1,6 +1,7 @@
 use clap::{ArgMatches, Command};
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 
+use crate::command::args::ProjectTarget;
 use crate::utils::docker::CommandType;
 
 /// Trait for handling docker compose commands
@@ -15,8 +16,19 @@ pub trait CommandHandler {
     /// Returns the CommandType for docker compose execution
     fn command_type(&self) -> CommandType;
 
-    /// Prepares command arguments from ArgMatches
-    fn prepare(&self, args: &ArgMatches) -> Vec<OsString>;
+    /// Prepares command arguments from ArgMatches, `target` telling which positional
+    /// values are projects
+    fn prepare(&self, args: &ArgMatches, target: ProjectTarget) -> Vec<OsString>;
+
+    /// Merges default arguments with the ones given in ArgMatches
+    fn merge_default_args(&self, default_args: &[&OsStr], args: &ArgMatches) -> Vec<OsString>;
+
+    /// Required positional arguments missing in ArgMatches
+    fn missing_required_args(
+        &self,
+        args: &ArgMatches,
+        target: ProjectTarget,
+    ) -> Vec<&'static str>;
 }
 
 // Declarative argument definition system
@@ -31,7 +43,10 @@ pub mod cd;
 pub mod completion;
 pub mod config;
 pub mod infos;
+pub mod migrate;
 pub mod register;
+pub mod schema;
+pub mod show;
 pub mod unregister;
 
 // Command registry (uses definitions.rs)
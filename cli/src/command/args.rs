@@ -3,8 +3,9 @@
 //! This module provides a type-safe, declarative way to define command arguments
 //! that automatically generates both the clap definition and the argument preparation.
 
+use clap::parser::ValueSource;
 use clap::{Arg, ArgAction, ArgMatches, Command};
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 
 /// Represents different types of command arguments
 #[derive(Clone)]
@@ -112,6 +113,47 @@ impl ArgDef {
         }
     }
 
+    /// Long option name, if any
+    pub fn long(&self) -> Option<&'static str> {
+        match self {
+            ArgDef::Flag { long, .. }
+            | ArgDef::Value { long, .. }
+            | ArgDef::Choice { long, .. }
+            | ArgDef::Number { long, .. } => Some(long),
+            _ => None,
+        }
+    }
+
+    /// Argument id, if any
+    pub fn id(&self) -> Option<&'static str> {
+        match self {
+            ArgDef::Flag { id, .. }
+            | ArgDef::Value { id, .. }
+            | ArgDef::Choice { id, .. }
+            | ArgDef::Number { id, .. } => Some(id),
+            _ => None,
+        }
+    }
+
+    /// Short option name, if any
+    pub fn short(&self) -> Option<char> {
+        match self {
+            ArgDef::Flag { short, .. }
+            | ArgDef::Value { short, .. }
+            | ArgDef::Choice { short, .. }
+            | ArgDef::Number { short, .. } => *short,
+            _ => None,
+        }
+    }
+
+    /// Whether the argument is followed by a value
+    pub fn takes_value(&self) -> bool {
+        matches!(
+            self,
+            ArgDef::Value { .. } | ArgDef::Choice { .. } | ArgDef::Number { .. }
+        )
+    }
+
     /// Extract argument value and add to args vector (owned strings)
     pub fn extract_to_args(&self, matches: &ArgMatches, args: &mut Vec<OsString>) {
         match self {
@@ -157,6 +199,31 @@ impl ArgDef {
     }
 }
 
+/// How the positional values of the command line target the projects
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectTarget {
+    /// The PROJECT values are aliases or groups, and the given number of first services
+    /// are more aliases, as in `dctl up web data tools`
+    Aliases(usize),
+    /// The command runs on the project of the current directory, the PROJECT value
+    /// being its first arg
+    CurrentDir,
+}
+
+impl Default for ProjectTarget {
+    fn default() -> Self {
+        ProjectTarget::Aliases(0)
+    }
+}
+
+/// Whether the projects are targeted by an option (--group, --all) instead of PROJECT
+pub fn has_project_selector(matches: &ArgMatches) -> bool {
+    let group = matches.try_get_one::<String>("GROUP").ok().flatten();
+    let all_projects = matches.try_get_one::<bool>("ALL_PROJECTS").ok().flatten();
+
+    group.is_some() || all_projects == Some(&true)
+}
+
 /// Command definition with arguments
 pub struct CommandDef {
     pub name: &'static str,
@@ -171,25 +238,190 @@ impl CommandDef {
     pub fn to_clap_command(&self) -> Command {
         let mut cmd = Command::new(self.name).about(self.about);
 
-        // Add PROJECT arg if needed
+        // Add PROJECT arg if needed, a group or all projects can be targeted instead,
+        // without any, the project is found from the current directory
         if self.needs_project {
-            cmd = cmd.arg(
-                Arg::new("PROJECT")
-                    .help("The name of the docker-compose file alias")
-                    .required(true),
-            );
+            let mut all_projects = Arg::new("ALL_PROJECTS")
+                .long("all-projects")
+                .help("Run the command on every registered project")
+                .action(ArgAction::SetTrue);
+            // Commands with their own --all option keep it
+            if !self.args.iter().any(|arg_def| arg_def.long() == Some("all")) {
+                all_projects = all_projects.visible_alias("all");
+            }
+
+            cmd = cmd
+                .arg(
+                    Arg::new("PROJECT")
+                        .help("The docker-compose file alias(es), comma separated, or @group")
+                        .value_delimiter(','),
+                )
+                .arg(
+                    Arg::new("GROUP")
+                        .long("group")
+                        .help("Run the command on every project of the group"),
+                )
+                .arg(all_projects);
         }
 
-        // Add all other args
+        // Add all other args, positionals can't be required after an optional PROJECT
         for arg_def in &self.args {
-            cmd = cmd.arg(arg_def.to_clap_arg());
+            let mut arg = arg_def.to_clap_arg();
+            if self.needs_project {
+                arg = arg.required(false);
+            }
+            cmd = cmd.arg(arg);
+        }
+
+        // Add a hidden --no-<flag> for flags without one, to cancel a default flag
+        for arg_def in &self.args {
+            if let ArgDef::Flag { id, long, .. } = arg_def {
+                let negative = format!("no-{}", long);
+                if !long.starts_with("no-") && self.find_by_long(&negative).is_none() {
+                    cmd = cmd.arg(
+                        Arg::new(format!("NO_{}", id))
+                            .long(negative)
+                            .help(format!("Cancel a default --{} argument", long))
+                            .action(ArgAction::SetTrue)
+                            .hide(true),
+                    );
+                }
+            }
         }
 
         cmd
     }
 
+    fn find_by_long(&self, long: &str) -> Option<&ArgDef> {
+        self.args.iter().find(|arg_def| arg_def.long() == Some(long))
+    }
+
+    fn find_by_short(&self, short: char) -> Option<&ArgDef> {
+        self.args.iter().find(|arg_def| arg_def.short() == Some(short))
+    }
+
+    /// Whether the argument, or its --no-<flag> counterpart, is given on the command line
+    fn is_overridden(&self, arg_def: &ArgDef, matches: &ArgMatches) -> bool {
+        let (Some(id), Some(long)) = (arg_def.id(), arg_def.long()) else {
+            return false;
+        };
+        let is_given = |id: &str| {
+            matches!(matches.try_get_raw(id), Ok(Some(_)))
+                && matches.value_source(id) == Some(ValueSource::CommandLine)
+        };
+
+        let counterpart = match long.strip_prefix("no-") {
+            Some(positive) => self.find_by_long(positive).and_then(|arg_def| arg_def.id()),
+            None => match self.find_by_long(&format!("no-{}", long)) {
+                Some(negative) => negative.id(),
+                None => None,
+            },
+        };
+
+        is_given(id)
+            || is_given(&format!("NO_{}", id))
+            || counterpart.is_some_and(is_given)
+    }
+
+    /// Merge the default args with the command line ones: a default arg is dropped
+    /// when the same arg, or its --no-<flag> counterpart, is given on the command line
+    pub fn merge_default_args(
+        &self,
+        default_args: &[&OsStr],
+        matches: &ArgMatches,
+    ) -> Vec<OsString> {
+        let mut merged: Vec<OsString> = Vec::new();
+        let mut default_args = default_args.iter();
+
+        while let Some(arg) = default_args.next() {
+            let raw = arg.to_string_lossy();
+            let (name, inline_value) = match raw.split_once('=') {
+                Some((name, value)) => (name, Some(value)),
+                None => (raw.as_ref(), None),
+            };
+
+            let arg_def = if let Some(long) = name.strip_prefix("--") {
+                self.find_by_long(long)
+            } else if let Some(short) = name.strip_prefix('-') {
+                let mut chars = short.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(short), None) => self.find_by_short(short),
+                    _ => None,
+                }
+            } else {
+                None
+            };
+
+            // Unknown args are kept as is
+            let Some(arg_def) = arg_def else {
+                merged.push(OsString::from(arg));
+                continue;
+            };
+
+            let mut group = vec![OsString::from(arg)];
+            if arg_def.takes_value() && inline_value.is_none() {
+                if let Some(value) = default_args.next() {
+                    group.push(OsString::from(value));
+                }
+            }
+
+            if !self.is_overridden(arg_def, matches) {
+                merged.append(&mut group);
+            }
+        }
+
+        merged
+    }
+
+    /// Whether the PROJECT value is the first positional arg of the command: when the
+    /// projects are targeted by an option, or by the current directory
+    fn has_project_arg(&self, matches: &ArgMatches, target: ProjectTarget) -> bool {
+        self.needs_project
+            && (target == ProjectTarget::CurrentDir || has_project_selector(matches))
+            && matches.get_many::<String>("PROJECT").is_some()
+    }
+
+    /// Required positional args without a value, checked once the projects are resolved
+    /// as clap can't require them after an optional PROJECT
+    pub fn missing_required_args(
+        &self,
+        matches: &ArgMatches,
+        target: ProjectTarget,
+    ) -> Vec<&'static str> {
+        let mut project_value = self.has_project_arg(matches, target);
+        let mut missing = Vec::new();
+
+        for arg_def in &self.args {
+            let (id, name) = match arg_def {
+                ArgDef::Container => ("CONTAINER", "<CONTAINER>"),
+                ArgDef::ServiceWithCommand => ("COMMAND_ARGS", "<COMMAND_ARGS>..."),
+                _ => continue,
+            };
+            if matches.get_many::<String>(id).is_some() {
+                continue;
+            }
+            if project_value {
+                project_value = false;
+            } else {
+                missing.push(name);
+            }
+        }
+
+        missing
+    }
+
     /// Prepare command arguments from matches (returns owned OsStrings)
     pub fn prepare_args(&self, matches: &ArgMatches) -> Vec<OsString> {
+        self.prepare_project_args(matches, ProjectTarget::default())
+    }
+
+    /// Prepare command arguments from matches, `target` telling which positional values
+    /// are projects, the PROJECT value being the first arg in the current directory
+    pub fn prepare_project_args(
+        &self,
+        matches: &ArgMatches,
+        target: ProjectTarget,
+    ) -> Vec<OsString> {
         let mut args: Vec<OsString> = vec![OsString::from(self.name)];
 
         // Extract flags and values first (before services/positional args)
@@ -200,10 +432,25 @@ impl CommandDef {
             }
         }
 
-        // Extract positional args last
+        // Extract positional args last, the PROJECT value first when it's not a project
+        if self.has_project_arg(matches, target) {
+            if let Some(values) = matches.get_many::<String>("PROJECT") {
+                args.push(OsString::from(values.cloned().collect::<Vec<_>>().join(",")));
+            }
+        }
         for arg_def in &self.args {
             match arg_def {
-                ArgDef::Services | ArgDef::Container | ArgDef::ServiceWithCommand => {
+                // The first services may be more projects
+                ArgDef::Services => {
+                    if let ProjectTarget::Aliases(projects) = target {
+                        if let Some(services) = matches.get_many::<String>("SERVICE") {
+                            args.extend(services.skip(projects).map(OsString::from));
+                        }
+                        continue;
+                    }
+                    arg_def.extract_to_args(matches, &mut args);
+                }
+                ArgDef::Container | ArgDef::ServiceWithCommand => {
                     arg_def.extract_to_args(matches, &mut args);
                 }
                 _ => {}
@@ -353,6 +600,157 @@ mod tests {
         assert_eq!(cmd.get_name(), "test");
     }
 
+    #[test]
+    fn test_command_def_accepts_a_group_instead_of_a_project() {
+        let cmd_def = CommandDef {
+            name: "test",
+            about: "Test command",
+            needs_project: true,
+            args: vec![],
+        };
+
+        let matches = cmd_def
+            .to_clap_command()
+            .try_get_matches_from(vec!["test", "--group", "front"])
+            .unwrap();
+        assert_eq!(matches.get_one::<String>("GROUP").unwrap(), "front");
+        assert!(matches.get_one::<String>("PROJECT").is_none());
+
+        // Without PROJECT, the project is found from the current directory
+        let matches = cmd_def.to_clap_command().try_get_matches_from(vec!["test"]).unwrap();
+        assert!(matches.get_one::<String>("PROJECT").is_none());
+    }
+
+    #[test]
+    fn test_prepare_args_with_a_group_gives_back_the_project_value() {
+        let cmd_def = CommandDef {
+            name: "logs",
+            about: "Logs command",
+            needs_project: true,
+            args: vec![
+                ArgDef::Flag {
+                    id: "FOLLOW",
+                    long: "follow",
+                    short: Some('f'),
+                    help: "Follow log output",
+                },
+                ArgDef::Services,
+            ],
+        };
+
+        let matches = cmd_def.to_clap_command().get_matches_from(vec![
+            "logs", "--group", "front", "-f", "php", "nginx",
+        ]);
+
+        let args = cmd_def.prepare_args(&matches);
+        assert_eq!(args, vec![
+            OsString::from("logs"),
+            OsString::from("--follow"),
+            OsString::from("php"),
+            OsString::from("nginx"),
+        ]);
+    }
+
+    #[test]
+    fn test_prepare_args_with_the_current_directory_project_gives_back_the_project_value() {
+        let cmd_def = CommandDef {
+            name: "logs",
+            about: "Logs command",
+            needs_project: true,
+            args: vec![ArgDef::Services],
+        };
+
+        let matches = cmd_def.to_clap_command().get_matches_from(vec!["logs", "php", "nginx"]);
+        assert_eq!(cmd_def.prepare_args(&matches), vec![
+            OsString::from("logs"),
+            OsString::from("nginx"),
+        ]);
+
+        let args = cmd_def.prepare_project_args(&matches, ProjectTarget::CurrentDir);
+        assert_eq!(args, vec![
+            OsString::from("logs"),
+            OsString::from("php"),
+            OsString::from("nginx"),
+        ]);
+
+        // The first services can be more projects
+        let matches =
+            cmd_def.to_clap_command().get_matches_from(vec!["logs", "web", "data", "nginx"]);
+        let args = cmd_def.prepare_project_args(&matches, ProjectTarget::Aliases(1));
+        assert_eq!(args, vec![OsString::from("logs"), OsString::from("nginx")]);
+    }
+
+    #[test]
+    fn test_missing_required_args_once_the_project_is_taken() {
+        let cmd_def = CommandDef {
+            name: "exec",
+            about: "Exec command",
+            needs_project: true,
+            args: vec![ArgDef::ServiceWithCommand],
+        };
+        let missing = |args: Vec<&str>| {
+            let matches = cmd_def.to_clap_command().get_matches_from(args);
+            cmd_def.missing_required_args(&matches, ProjectTarget::default())
+        };
+
+        assert_eq!(missing(vec!["exec", "shop"]), vec!["<COMMAND_ARGS>..."]);
+        assert_eq!(missing(vec!["exec", "--group", "front"]), vec!["<COMMAND_ARGS>..."]);
+        assert!(missing(vec!["exec", "shop", "php", "bash"]).is_empty());
+        assert!(missing(vec!["exec", "--group", "front", "php"]).is_empty());
+
+        // In a project directory, PROJECT holds the service
+        let matches = cmd_def.to_clap_command().get_matches_from(vec!["exec", "php"]);
+        assert_eq!(cmd_def.missing_required_args(&matches, ProjectTarget::default()), vec!["<COMMAND_ARGS>..."]);
+        assert!(cmd_def.missing_required_args(&matches, ProjectTarget::CurrentDir).is_empty());
+    }
+
+    #[test]
+    fn test_command_def_accepts_all_projects() {
+        let cmd_def = CommandDef {
+            name: "down",
+            about: "Down command",
+            needs_project: true,
+            args: vec![],
+        };
+
+        let matches = cmd_def
+            .to_clap_command()
+            .try_get_matches_from(vec!["down", "--all"])
+            .unwrap();
+        assert!(matches.get_flag("ALL_PROJECTS"));
+        assert!(has_project_selector(&matches));
+
+        let matches = cmd_def
+            .to_clap_command()
+            .try_get_matches_from(vec!["down", "web,data"])
+            .unwrap();
+        let projects: Vec<&String> = matches.get_many::<String>("PROJECT").unwrap().collect();
+        assert_eq!(projects, vec!["web", "data"]);
+        assert!(!has_project_selector(&matches));
+    }
+
+    #[test]
+    fn test_command_def_keeps_its_own_all_option() {
+        let cmd_def = CommandDef {
+            name: "ps",
+            about: "Ps command",
+            needs_project: true,
+            args: vec![ArgDef::Flag {
+                id: "ALL",
+                long: "all",
+                short: Some('a'),
+                help: "Show all stopped containers",
+            }],
+        };
+
+        let matches = cmd_def
+            .to_clap_command()
+            .try_get_matches_from(vec!["ps", "--all", "--all-projects"])
+            .unwrap();
+        assert!(matches.get_flag("ALL"));
+        assert!(matches.get_flag("ALL_PROJECTS"));
+    }
+
     #[test]
     fn test_prepare_args_with_flags() {
         let cmd_def = CommandDef {
@@ -419,6 +817,84 @@ mod tests {
         ]);
     }
 
+    fn get_up_def() -> CommandDef {
+        CommandDef {
+            name: "up",
+            about: "Up command",
+            needs_project: true,
+            args: vec![
+                flag!("BUILD", "build", "Build images"),
+                flag!("NO_BUILD", "no-build", "Don't build images"),
+                flag!("DETACH", "detach", 'd', "Detached mode"),
+                choice!("PULL", "pull", "Pull image", ["always", "missing"]),
+                number!("TIMEOUT", "timeout", 't', "Shutdown timeout"),
+                ArgDef::Services,
+            ],
+        }
+    }
+
+    fn merge_default_args(
+        cmd_def: &CommandDef,
+        default_args: &[&str],
+        args: Vec<&str>,
+    ) -> Vec<OsString> {
+        let matches = cmd_def.to_clap_command().get_matches_from(args);
+        let default_args: Vec<&OsStr> = default_args.iter().map(OsStr::new).collect();
+        cmd_def.merge_default_args(&default_args, &matches)
+    }
+
+    #[test]
+    fn test_merge_default_args_keeps_defaults_not_given() {
+        let cmd_def = get_up_def();
+        let args = merge_default_args(
+            &cmd_def,
+            &["-d", "--timeout", "10", "--remove-orphans"],
+            vec!["up", "--build", "myproject"],
+        );
+        assert_eq!(args, vec!["-d", "--timeout", "10", "--remove-orphans"]);
+    }
+
+    #[test]
+    fn test_merge_default_args_replaces_values_given() {
+        let cmd_def = get_up_def();
+        let args = merge_default_args(
+            &cmd_def,
+            &["-d", "--timeout", "10", "--pull=always"],
+            vec!["up", "-t", "30", "--pull", "missing", "myproject"],
+        );
+        assert_eq!(args, vec!["-d"]);
+    }
+
+    #[test]
+    fn test_merge_default_args_drops_opposite_flags() {
+        let cmd_def = get_up_def();
+
+        let args =
+            merge_default_args(&cmd_def, &["--build"], vec!["up", "--no-build", "myproject"]);
+        assert!(args.is_empty());
+
+        let args =
+            merge_default_args(&cmd_def, &["--no-build"], vec!["up", "--build", "myproject"]);
+        assert!(args.is_empty());
+    }
+
+    #[test]
+    fn test_merge_default_args_cancels_a_default_flag() {
+        let cmd_def = get_up_def();
+        let args = merge_default_args(
+            &cmd_def,
+            &["-d", "--build"],
+            vec!["up", "--no-detach", "myproject"],
+        );
+        assert_eq!(args, vec!["--build"]);
+
+        // The cancel flag is not sent to docker compose
+        let matches = cmd_def
+            .to_clap_command()
+            .get_matches_from(vec!["up", "--no-detach", "myproject"]);
+        assert_eq!(cmd_def.prepare_args(&matches), vec![OsString::from("up")]);
+    }
+
     #[test]
     fn test_flag_macro() {
         let flag = flag!("TEST", "test", "Test flag");
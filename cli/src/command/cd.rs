@@ -45,9 +45,10 @@ mod tests {
             alias: String::from("test"),
             description: None,
             compose_files: vec![String::from("/home/test/test/docker-compose.yml")],
-            enviroment_file: None,
+            environment_file: None,
             use_project_name: None,
             status: None,
+            ..Default::default()
         };
 
         assert_eq!(
@@ -65,9 +66,10 @@ mod tests {
                 String::from("/first/path/docker-compose.yml"),
                 String::from("/second/path/docker-compose.yml"),
             ],
-            enviroment_file: None,
+            environment_file: None,
             use_project_name: None,
             status: None,
+            ..Default::default()
         };
 
         // Should return the first file's path
@@ -83,9 +85,10 @@ mod tests {
             alias: String::from("test"),
             description: None,
             compose_files: vec![],
-            enviroment_file: None,
+            environment_file: None,
             use_project_name: None,
             status: None,
+            ..Default::default()
         };
 
         let result = extract_path_from_cd_command(&item);
@@ -102,9 +105,10 @@ mod tests {
             alias: String::from("test"),
             description: None,
             compose_files: vec![String::from("relative/path/docker-compose.yml")],
-            enviroment_file: None,
+            environment_file: None,
             use_project_name: None,
             status: None,
+            ..Default::default()
         };
 
         assert_eq!(
@@ -4,9 +4,13 @@ use std::str::from_utf8;
 use clap::{Arg, ArgMatches, Command};
 use anyhow::{Context, Result};
 
+use crate::command::args::ProjectTarget;
 use crate::command::definitions::config_def;
 use crate::parser::config::{CliConfig, ComposeItem};
 use crate::utils::docker::{CommandOutput, CommandType, Container};
+use crate::utils::engine::get_project_container;
+use crate::utils::dry_run::{format_command_line, get_dry_run_format, is_dry_run, DryRunFormat};
+use crate::utils::secret::{mask_env, resolve_env};
 
 pub fn check_config() -> Command {
     Command::new("check-config")
@@ -26,6 +30,7 @@ pub async fn exec_check_config(
     args: &ArgMatches,
 ) -> Result<()> {
     let validate_syntax = args.get_flag("VALIDATE");
+    let dry_run = is_dry_run(args);
 
     // Check docker bin path
     let config_docker_bin_path = config.get_container_bin_path()?;
@@ -53,6 +58,12 @@ pub async fn exec_check_config(
             has_error = true;
         }
 
+        // In dry-run mode, print the validation command instead of running it
+        if validate_syntax && !item_has_errors && dry_run {
+            print_validate_command(&item, container, get_dry_run_format(args))?;
+            continue;
+        }
+
         // Validate syntax if requested and files exist
         if validate_syntax && !item_has_errors {
             let syntax_errors = validate_compose_syntax(&item, container).await?;
@@ -69,7 +80,7 @@ pub async fn exec_check_config(
     }
 
     if !has_error {
-        if validate_syntax {
+        if validate_syntax && !dry_run {
             println!("✅ - No errors found (files exist and syntax is valid)");
         } else {
             println!("✅ - No errors found");
@@ -79,6 +90,35 @@ pub async fn exec_check_config(
     Ok(())
 }
 
+/// Args of `docker compose config --quiet`, validating the syntax of a project
+fn get_validate_args(item: &ComposeItem) -> Result<ArgMatches> {
+    Ok(config_def()
+        .to_clap_command()
+        .try_get_matches_from(vec!["config", "--quiet", &item.alias])?)
+}
+
+/// Print the command validating the syntax of a project, in dry-run mode
+fn print_validate_command(
+    item: &ComposeItem,
+    container: &dyn Container,
+    format: DryRunFormat,
+) -> Result<()> {
+    let project_container = get_project_container(container, item);
+    let container = project_container.as_deref().unwrap_or(container);
+    let config_args = ComposeItem::to_args(item);
+    let command_line = container.command_line(
+        CommandType::Config,
+        &config_args,
+        &[],
+        &get_validate_args(item)?,
+        ProjectTarget::default(),
+    )?;
+    let config_env = mask_env(&item.to_env(container.engine()));
+    println!("{}", format_command_line(&item.alias, &config_env, &command_line, format));
+
+    Ok(())
+}
+
 /// Validate docker-compose file syntax using `docker compose config --quiet`
 async fn validate_compose_syntax(
     item: &ComposeItem,
@@ -86,20 +126,28 @@ async fn validate_compose_syntax(
 ) -> Result<Vec<String>> {
     let mut error_list: Vec<String> = Vec::new();
 
+    let project_container = get_project_container(container, item);
+    let container = project_container.as_deref().unwrap_or(container);
     let config_args = ComposeItem::to_args(item);
-
-    // Get the config command definition
-    let config_command = config_def().to_clap_command();
+    let config_env = match resolve_env(&item.alias, &item.to_env(container.engine())).await {
+        Ok(config_env) => config_env,
+        Err(e) => {
+            error_list.push(format!("❌ - {}", e));
+            return Ok(error_list);
+        }
+    };
 
     // Run docker compose config --quiet to validate syntax
-    let args = config_command.try_get_matches_from(vec!["config", "--quiet", &item.alias])?;
+    let args = get_validate_args(item)?;
 
     let result = container
         .compose(
             CommandType::Config,
             &config_args,
-            &vec![],
+            &config_env,
+            &[],
             &args,
+            ProjectTarget::default(),
             Some(CommandOutput::Output),
         )
         .await;
@@ -123,8 +171,15 @@ async fn validate_compose_syntax(
 pub fn check_item_config(item: &ComposeItem) -> Result<Vec<String>> {
     let mut error_list: Vec<String> = Vec::new();
 
-    if let Some(env_file) = &item.enviroment_file {
-        let file_path = Path::new(&env_file);
+    if let Some(docker_bin) = &item.docker_bin {
+        if !check_docker_bin_path(docker_bin)? {
+            error_list.push(format!("❌ - Docker bin path: {}", docker_bin));
+        }
+    }
+
+    // A missing optional env file is skipped
+    for (env_file, _) in item.get_env_files().into_iter().filter(|(_, required)| *required) {
+        let file_path = Path::new(env_file);
         if !file_path.exists() {
             error_list.push(format!("❌ - env file: {:?}", file_path));
         }
@@ -148,7 +203,7 @@ fn check_docker_bin_path(config_docker_bin_path: &str) -> Result<bool> {
 
 #[cfg(test)]
 mod tests {
-    use crate::parser::config::ComposeStatus;
+    use crate::parser::config::{ComposeStatus, EnvFile};
 
     use super::*;
 
@@ -179,11 +234,12 @@ mod tests {
     fn test_check_item_config_valid_compose_file() {
         let item = ComposeItem {
             alias: "test".to_string(),
-            enviroment_file: None,
+            environment_file: None,
             compose_files: vec!["tests/docker-compose.test.yml".to_string()],
             description: Some("test".to_string()),
             status: Some(ComposeStatus::Running),
             use_project_name: Some(false),
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
@@ -194,11 +250,12 @@ mod tests {
     fn test_check_item_config_missing_files() {
         let item = ComposeItem {
             alias: "test".to_string(),
-            enviroment_file: Some("tests/.env".to_string()),
+            environment_file: Some("tests/.env".to_string()),
             compose_files: vec!["tests/docker-compose.yml".to_string()],
             description: Some("test".to_string()),
             status: Some(ComposeStatus::Running),
             use_project_name: Some(false),
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
@@ -212,11 +269,12 @@ mod tests {
     fn test_check_item_config_missing_compose_only() {
         let item = ComposeItem {
             alias: "test".to_string(),
-            enviroment_file: None,
+            environment_file: None,
             compose_files: vec!["nonexistent/docker-compose.yml".to_string()],
             description: None,
             status: None,
             use_project_name: None,
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
@@ -225,11 +283,25 @@ mod tests {
         assert!(errors[0].contains("Compose file"));
     }
 
+    #[test]
+    fn test_check_item_config_missing_project_docker_bin() {
+        let item = ComposeItem {
+            alias: "test".to_string(),
+            compose_files: vec!["tests/docker-compose.test.yml".to_string()],
+            docker_bin: Some("/nonexistent/docker".to_string()),
+            ..Default::default()
+        };
+
+        let errors = check_item_config(&item).unwrap();
+
+        assert_eq!(errors, vec!["❌ - Docker bin path: /nonexistent/docker"]);
+    }
+
     #[test]
     fn test_check_item_config_multiple_compose_files() {
         let item = ComposeItem {
             alias: "test".to_string(),
-            enviroment_file: None,
+            environment_file: None,
             compose_files: vec![
                 "nonexistent1.yml".to_string(),
                 "nonexistent2.yml".to_string(),
@@ -237,6 +309,7 @@ mod tests {
             description: None,
             status: None,
             use_project_name: None,
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
@@ -262,11 +335,12 @@ mod tests {
     fn test_check_item_config_empty_compose_files() {
         let item = ComposeItem {
             alias: "test".to_string(),
-            enviroment_file: None,
+            environment_file: None,
             compose_files: vec![],
             description: None,
             status: None,
             use_project_name: None,
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
@@ -277,11 +351,12 @@ mod tests {
     fn test_check_item_config_missing_env_file_only() {
         let item = ComposeItem {
             alias: "test".to_string(),
-            enviroment_file: Some("/nonexistent/.env".to_string()),
+            environment_file: Some("/nonexistent/.env".to_string()),
             compose_files: vec!["tests/docker-compose.test.yml".to_string()],
             description: None,
             status: None,
             use_project_name: None,
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
@@ -290,16 +365,38 @@ mod tests {
         assert!(errors[0].contains("env file"));
     }
 
+    #[test]
+    fn test_check_item_config_skips_optional_env_files() {
+        let item = ComposeItem {
+            alias: "test".to_string(),
+            compose_files: vec!["tests/docker-compose.test.yml".to_string()],
+            env_files: Some(vec![
+                EnvFile::File {
+                    path: "/nonexistent/.env.local".to_string(),
+                    required: Some(false),
+                },
+                EnvFile::Path("/nonexistent/.env".to_string()),
+            ]),
+            ..Default::default()
+        };
+
+        let errors = check_item_config(&item).unwrap();
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("/nonexistent/.env\""));
+    }
+
     #[test]
     fn test_check_item_config_all_files_exist() {
         // Both env and compose files exist
         let item = ComposeItem {
             alias: "test".to_string(),
-            enviroment_file: Some("tests/test.env".to_string()),
+            environment_file: Some("tests/test.env".to_string()),
             compose_files: vec!["tests/docker-compose.test.yml".to_string()],
             description: Some("valid project".to_string()),
             status: Some(ComposeStatus::Stopped),
             use_project_name: Some(true),
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
@@ -324,11 +421,12 @@ mod tests {
     fn test_check_item_config_with_partial_running_status() {
         let item = ComposeItem {
             alias: "partial".to_string(),
-            enviroment_file: None,
+            environment_file: None,
             compose_files: vec!["tests/docker-compose.test.yml".to_string()],
             description: Some("partial running".to_string()),
             status: Some(ComposeStatus::PartialRunning),
             use_project_name: Some(true),
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
@@ -339,11 +437,12 @@ mod tests {
     fn test_check_item_config_with_config_error_status() {
         let item = ComposeItem {
             alias: "error".to_string(),
-            enviroment_file: None,
+            environment_file: None,
             compose_files: vec!["/nonexistent/docker-compose.yml".to_string()],
             description: None,
             status: Some(ComposeStatus::ConfigError),
             use_project_name: None,
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
@@ -355,7 +454,7 @@ mod tests {
     fn test_check_item_config_mixed_existing_and_missing() {
         let item = ComposeItem {
             alias: "mixed".to_string(),
-            enviroment_file: None,
+            environment_file: None,
             compose_files: vec![
                 "tests/docker-compose.test.yml".to_string(), // exists
                 "/nonexistent/override.yml".to_string(),     // doesn't exist
@@ -363,6 +462,7 @@ mod tests {
             description: None,
             status: None,
             use_project_name: None,
+            ..Default::default()
         };
 
         let errors = check_item_config(&item).unwrap();
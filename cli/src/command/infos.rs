@@ -1,13 +1,17 @@
 use std::str::from_utf8;
 
-use clap::Command;
+use clap::{ArgMatches, Command};
 use anyhow::{Context, Result};
 use futures::future::join_all;
 use tabled::{Table, settings::{Margin, Style}};
 
+use crate::command::args::ProjectTarget;
 use crate::command::{definitions::ps_def, config::check_item_config};
 use crate::parser::config::{CliConfig, ComposeItem};
 use crate::utils::docker::{CommandOutput, CommandType, Container};
+use crate::utils::engine::get_project_container;
+use crate::utils::dry_run::{format_command_line, get_dry_run_format, is_dry_run, DryRunFormat};
+use crate::utils::secret::skip_secrets;
 
 pub fn projects_infos() -> Command {
     Command::new("infos").about("Describe all projects with their status")
@@ -24,19 +28,23 @@ async fn check_project_status(
         return Ok((-1, -1)); // Config error
     }
 
+    let project_container = get_project_container(container, item);
+    let container = project_container.as_deref().unwrap_or(container);
     let config_args = ComposeItem::to_args(item);
-    let ps_command = ps_def().to_clap_command();
+    // The secrets are not needed to list the containers, and their commands could prompt
+    // at the same time for all the projects
+    let config_env = skip_secrets(&item.to_env(container.engine()));
+    let [args_all, args_run] = get_ps_args(item)?;
 
     // Get all containers for this project
-    let args_all = ps_command
-        .clone()
-        .try_get_matches_from(vec!["ps", "-a", "-q", &item.alias])?;
     let ps_all = container
         .compose(
             CommandType::Ps,
             &config_args,
-            &vec![],
+            &config_env,
+            &[],
             &args_all,
+            ProjectTarget::default(),
             Some(CommandOutput::Output),
         )
         .await?;
@@ -44,13 +52,14 @@ async fn check_project_status(
     let all_containers_count = output_all.lines().count();
 
     // Get running containers for this project
-    let args_run = ps_command.try_get_matches_from(vec!["ps", "-q", &item.alias])?;
     let ps_run = container
         .compose(
             CommandType::Ps,
             &config_args,
-            &vec![],
+            &config_env,
+            &[],
             &args_run,
+            ProjectTarget::default(),
             Some(CommandOutput::Output),
         )
         .await?;
@@ -63,12 +72,59 @@ async fn check_project_status(
     ))
 }
 
+/// Args of the ps commands listing all the containers of a project, then the running ones
+fn get_ps_args(item: &ComposeItem) -> Result<[ArgMatches; 2]> {
+    let ps_command = ps_def().to_clap_command();
+
+    Ok([
+        ps_command.clone().try_get_matches_from(vec!["ps", "-a", "-q", &item.alias])?,
+        ps_command.try_get_matches_from(vec!["ps", "-q", &item.alias])?,
+    ])
+}
+
+/// Print the ps commands checking the status of a project, in dry-run mode
+fn print_project_status_commands(
+    item: &ComposeItem,
+    container: &dyn Container,
+    format: DryRunFormat,
+) -> Result<()> {
+    let project_container = get_project_container(container, item);
+    let container = project_container.as_deref().unwrap_or(container);
+    let config_args = ComposeItem::to_args(item);
+    let config_env = skip_secrets(&item.to_env(container.engine()));
+
+    for args in get_ps_args(item)? {
+        let command_line = container.command_line(
+            CommandType::Ps,
+            &config_args,
+            &[],
+            &args,
+            ProjectTarget::default(),
+        )?;
+        println!("{}", format_command_line(&item.alias, &config_env, &command_line, format));
+    }
+
+    Ok(())
+}
+
 pub async fn exec_projects_infos(
     config: &mut dyn CliConfig,
     container: &dyn Container,
+    args: &ArgMatches,
 ) -> Result<()> {
     let mut items = config.get_all_compose_items();
 
+    // Print the commands instead of running them, projects with a config error are skipped
+    if is_dry_run(args) {
+        let format = get_dry_run_format(args);
+        for item in &items {
+            if check_item_config(item)?.is_empty() {
+                print_project_status_commands(item, container, format)?;
+            }
+        }
+        return Ok(());
+    }
+
     // Create futures for all project status checks
     let futures: Vec<_> = items
         .iter()
@@ -79,10 +135,15 @@ pub async fn exec_projects_infos(
     let results = join_all(futures).await;
 
     // Update items with results
-    for (item, result) in items.iter_mut().zip(results.into_iter()) {
+    let mut errors: Vec<String> = Vec::new();
+    for (item, result) in items.iter_mut().zip(results) {
         match result {
             Ok((running, total)) => item.set_status(running, total),
-            Err(_) => item.set_status(-1, -1), // Mark as error on failure
+            Err(e) => {
+                // Mark as error on failure
+                item.set_status(-1, -1);
+                errors.push(format!("❌ - {}: {}", item.alias, e));
+            }
         }
     }
 
@@ -93,6 +154,9 @@ pub async fn exec_projects_infos(
             .with(Style::modern())
             .with(Margin::new(0, 0, 1, 1))
     );
+    for error in errors {
+        eprintln!("{}", error);
+    }
 
     Ok(())
 }
\ No newline at end of file
@@ -0,0 +1,97 @@
+use std::fs;
+
+use clap::{Arg, ArgMatches, Command};
+use anyhow::{Context, Result};
+use toml_edit::DocumentMut;
+
+use crate::command::register::get_config_path;
+use crate::parser::migrate::{migrate, CONFIG_VERSION};
+use crate::utils::diff::unified_diff;
+use crate::utils::dry_run::is_dry_run;
+
+pub fn migrate_config() -> Command {
+    Command::new("migrate")
+        .about("Update config files to the current format, keeping their comments")
+        .arg(
+            Arg::new("FILES")
+                .help("The config files to update, the user config file by default")
+                .num_args(1..),
+        )
+}
+
+pub fn exec_migrate_config(args: &ArgMatches) -> Result<()> {
+    let files: Vec<String> = match args.get_many::<String>("FILES") {
+        Some(files) => files.cloned().collect(),
+        None => vec![get_config_path()],
+    };
+    let dry_run = is_dry_run(args);
+
+    for file in files {
+        migrate_config_file(&file, dry_run)?;
+    }
+
+    Ok(())
+}
+
+/// Rewrite a config file in the current format and print the diff, without
+/// writing it in dry-run mode
+fn migrate_config_file(file: &str, dry_run: bool) -> Result<()> {
+    let path = shellexpand::tilde(file).to_string();
+    let content = fs::read_to_string(&path)
+        .context(format!("Failed to read config file: {}", path))?;
+    let mut doc = content
+        .parse::<DocumentMut>()
+        .context(format!("Failed to parse config file: {}", path))?;
+
+    let changes = migrate(&mut doc);
+    let migrated = doc.to_string();
+    if migrated == content {
+        println!("{} is up to date", file);
+        return Ok(());
+    }
+
+    print!("{}", unified_diff(&content, &migrated, file));
+    if dry_run {
+        println!("{} would be migrated to the config version {}", file, CONFIG_VERSION);
+    } else {
+        fs::write(&path, &migrated).context(format!("Failed to write config file: {}", path))?;
+        println!("{} migrated to the config version {}", file, CONFIG_VERSION);
+    }
+    for change in changes {
+        println!("  - {}", change);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_migrates_a_config_file_in_place() {
+        let path = std::env::temp_dir().join("dctl_migrate_test.toml");
+        fs::write(&path, "[[collections]]\nalias = \"web\"\nenviroment_file = \".env\"\n").unwrap();
+
+        migrate_config_file(path.to_str().unwrap(), false).unwrap();
+        let content = fs::read_to_string(&path).unwrap();
+        assert!(content.starts_with("version = 2\n"));
+        assert!(content.contains("environment_file = \".env\""));
+
+        // An up to date file is left as is
+        migrate_config_file(path.to_str().unwrap(), false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn it_does_not_write_the_config_file_in_dry_run_mode() {
+        let path = std::env::temp_dir().join("dctl_migrate_dry_run_test.toml");
+        let content = "[main]\ndocker_bin = \"docker\"\n";
+        fs::write(&path, content).unwrap();
+
+        migrate_config_file(path.to_str().unwrap(), true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), content);
+        fs::remove_file(&path).unwrap();
+    }
+}
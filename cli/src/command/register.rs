@@ -4,9 +4,13 @@ use std::path::Path;
 
 use clap::{Arg, ArgMatches, Command};
 use anyhow::{anyhow, Context, Result};
-use toml_edit::{Array, DocumentMut, Item, Table, Value};
+use toml_edit::{value, Array, ArrayOfTables, DocumentMut, Item, Table, Value};
 
-use crate::parser::config::CliConfig;
+use crate::parser::config::{normalize_path, CliConfig};
+use crate::parser::migrate::CONFIG_VERSION;
+use crate::utils::diff::unified_diff;
+use crate::utils::dry_run::is_dry_run;
+use crate::utils::error::DctlError;
 
 pub fn register_project() -> Command {
     Command::new("register")
@@ -38,7 +42,7 @@ pub fn register_project() -> Command {
         )
 }
 
-fn get_config_path() -> String {
+pub fn get_config_path() -> String {
     env::var("DCTL_CONFIG_FILE_PATH")
         .unwrap_or_else(|_| String::from("~/.config/dctl/config.toml"))
 }
@@ -47,13 +51,49 @@ fn expand_path(path: &str) -> String {
     shellexpand::tilde(path).to_string()
 }
 
+/// Make a path given on the command line absolute, as the relative paths of
+/// the config file are relative to its directory
+fn absolute_path(path: &str) -> String {
+    if path.starts_with('~') {
+        return path.to_string();
+    }
+    match std::path::absolute(path) {
+        Ok(path) => normalize_path(&path).to_string_lossy().to_string(),
+        Err(_) => path.to_string(),
+    }
+}
+
+/// Add a project to the content of a config file, an empty content being a new file
+/// in the current config version, and the `collections` array created when missing
+fn add_collection(content: &str, collection: Table) -> Result<String> {
+    let mut doc = content
+        .parse::<DocumentMut>()
+        .context("Failed to parse config file")?;
+    if content.trim().is_empty() {
+        doc["version"] = value(CONFIG_VERSION);
+    }
+
+    let collections = doc
+        .entry("collections")
+        .or_insert(Item::ArrayOfTables(ArrayOfTables::new()));
+    match collections.as_array_of_tables_mut() {
+        Some(collections) => collections.push(collection),
+        None => {
+            return Err(anyhow!("Invalid config format: 'collections' is not an array of tables"))
+        }
+    }
+
+    Ok(doc.to_string())
+}
+
 pub fn exec_register_project(config: &dyn CliConfig, args: &ArgMatches) -> Result<()> {
     let alias = args.get_one::<String>("ALIAS").unwrap();
-    let compose_files_args: Vec<&String> = args
+    let compose_files_args: Vec<String> = args
         .get_many::<String>("COMPOSE_FILES")
         .unwrap()
+        .map(|file| absolute_path(file))
         .collect();
-    let env_file = args.get_one::<String>("ENV_FILE");
+    let env_file = args.get_one::<String>("ENV_FILE").map(|env| absolute_path(env));
     let description = args.get_one::<String>("DESCRIPTION");
 
     // Check if alias already exists
@@ -65,26 +105,36 @@ pub fn exec_register_project(config: &dyn CliConfig, args: &ArgMatches) -> Resul
     for compose_file in &compose_files_args {
         let compose_path = expand_path(compose_file);
         if !Path::new(&compose_path).exists() {
-            return Err(anyhow!("Compose file does not exist: {}", compose_file));
+            return Err(DctlError::MissingFile {
+                kind: String::from("Compose file"),
+                path: compose_file.to_string(),
+            }
+            .into());
         }
     }
 
     // Validate env file if provided
-    if let Some(env) = env_file {
+    if let Some(env) = &env_file {
         let env_path = expand_path(env);
         if !Path::new(&env_path).exists() {
-            return Err(anyhow!("Environment file does not exist: {}", env));
+            return Err(DctlError::MissingFile {
+                kind: String::from("Environment file"),
+                path: env.to_string(),
+            }
+            .into());
         }
     }
 
-    // Read and modify config file
+    // Read the user config file, created when missing as the projects may come from
+    // the other config files
     let config_path = expand_path(&get_config_path());
-    let config_content = fs::read_to_string(&config_path)
-        .context(format!("Failed to read config file: {}", config_path))?;
-
-    let mut doc = config_content
-        .parse::<DocumentMut>()
-        .context("Failed to parse config file")?;
+    let config_content = match fs::read_to_string(&config_path) {
+        Ok(content) => content,
+        Err(err) if err.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(err) => {
+            return Err(err).context(format!("Failed to read config file: {}", config_path))
+        }
+    };
 
     // Create new collection entry
     let mut new_collection = Table::new();
@@ -94,8 +144,8 @@ pub fn exec_register_project(config: &dyn CliConfig, args: &ArgMatches) -> Resul
         new_collection.insert("description", Value::from(desc.as_str()).into());
     }
 
-    if let Some(env) = env_file {
-        new_collection.insert("enviroment_file", Value::from(env.as_str()).into());
+    if let Some(env) = &env_file {
+        new_collection.insert("environment_file", Value::from(env.as_str()).into());
     }
 
     let mut compose_files = Array::new();
@@ -103,20 +153,21 @@ pub fn exec_register_project(config: &dyn CliConfig, args: &ArgMatches) -> Resul
         compose_files.push(file.as_str());
     }
     new_collection.insert("compose_files", Item::Value(Value::Array(compose_files)));
+    let new_content = add_collection(&config_content, new_collection)?;
 
-    // Add to collections array
-    if let Some(collections) = doc.get_mut("collections") {
-        if let Some(arr) = collections.as_array_of_tables_mut() {
-            arr.push(new_collection);
-        } else {
-            return Err(anyhow!("Invalid config format: 'collections' is not an array of tables"));
-        }
-    } else {
-        return Err(anyhow!("Invalid config format: missing 'collections' section"));
+    // Print the change instead of writing it in dry-run mode
+    if is_dry_run(args) {
+        print!("{}", unified_diff(&config_content, &new_content, &config_path));
+        println!("Project '{}' would be registered", alias);
+        return Ok(());
     }
 
     // Write back to file
-    fs::write(&config_path, doc.to_string())
+    if let Some(config_dir) = Path::new(&config_path).parent() {
+        fs::create_dir_all(config_dir)
+            .context(format!("Failed to create config directory: {}", config_dir.display()))?;
+    }
+    fs::write(&config_path, new_content)
         .context(format!("Failed to write config file: {}", config_path))?;
 
     println!("Project '{}' registered successfully", alias);
@@ -138,6 +189,8 @@ pub fn exec_register_project(config: &dyn CliConfig, args: &ArgMatches) -> Resul
 mod tests {
     use super::*;
     use crate::parser::config::{ComposeItem, DefaultCommandArgs};
+    use crate::parser::origin::Origin;
+    use crate::utils::engine::Engine;
 
     // Mock CliConfig for testing
     struct MockConfig {
@@ -149,25 +202,42 @@ mod tests {
             Ok("/usr/bin/docker".to_string())
         }
 
+        fn get_engine(&self) -> Engine {
+            Engine::Docker
+        }
+
         fn get_compose_item_by_alias(&self, alias: String) -> Option<ComposeItem> {
             if self.existing_aliases.contains(&alias) {
                 Some(ComposeItem {
                     alias,
                     description: None,
                     compose_files: vec![],
-                    enviroment_file: None,
+                    environment_file: None,
                     use_project_name: None,
                     status: None,
+                    ..Default::default()
                 })
             } else {
                 None
             }
         }
 
+        fn get_compose_items_by_group(&self, _group: String) -> Vec<ComposeItem> {
+            vec![]
+        }
+
         fn get_all_compose_items(&self) -> Vec<ComposeItem> {
             vec![]
         }
 
+        fn to_toml(&self) -> Result<toml::Table> {
+            Ok(toml::Table::new())
+        }
+
+        fn get_origin(&self, _alias: Option<&str>, _key: &str) -> Option<Origin> {
+            None
+        }
+
         fn get_default_command_args(&self, _command_name: &str) -> Option<DefaultCommandArgs> {
             None
         }
@@ -306,6 +376,17 @@ mod tests {
         assert_eq!(path, "/path/~/test");
     }
 
+    #[test]
+    fn test_absolute_path() {
+        let current_dir = env::current_dir().unwrap();
+        assert_eq!(absolute_path("/absolute/../path"), "/path");
+        assert_eq!(absolute_path("~/test"), "~/test");
+        assert_eq!(
+            absolute_path("./tests/docker-compose.test.yml"),
+            current_dir.join("tests/docker-compose.test.yml").to_string_lossy()
+        );
+    }
+
     #[test]
     fn test_get_config_path_default() {
         // Clear env var to test default
@@ -322,6 +403,30 @@ mod tests {
         env::remove_var("DCTL_CONFIG_FILE_PATH");
     }
 
+    fn get_collection() -> Table {
+        let mut collection = Table::new();
+        collection.insert("alias", Value::from("shop").into());
+        collection
+    }
+
+    #[test]
+    fn test_add_collection_to_a_new_config_file() {
+        let content = add_collection("", get_collection()).unwrap();
+        assert_eq!(content, "version = 2\n\n[[collections]]\nalias = \"shop\"\n");
+    }
+
+    #[test]
+    fn test_add_collection_to_a_config_file_without_collections() {
+        let content = "[main]\ndocker_bin = \"docker\"\n";
+        assert_eq!(
+            add_collection(content, get_collection()).unwrap(),
+            "[main]\ndocker_bin = \"docker\"\n\n[[collections]]\nalias = \"shop\"\n"
+        );
+
+        let error = add_collection("collections = 1\n", get_collection()).unwrap_err();
+        assert!(error.to_string().contains("'collections' is not an array of tables"));
+    }
+
     #[test]
     fn test_exec_register_alias_already_exists() {
         let config = MockConfig {
@@ -4,9 +4,10 @@
 //! from definitions.rs, eliminating the need for individual command files.
 
 use clap::{ArgMatches, Command};
-use std::ffi::OsString;
+use std::ffi::{OsStr, OsString};
 
 use crate::utils::docker::CommandType;
+use super::args::ProjectTarget;
 use super::CommandHandler;
 use super::definitions::*;
 
@@ -28,8 +29,24 @@ macro_rules! define_command_from_def {
                 CommandType::$command_type
             }
 
-            fn prepare(&self, args: &ArgMatches) -> Vec<OsString> {
-                $def_fn().prepare_args(args)
+            fn prepare(&self, args: &ArgMatches, target: ProjectTarget) -> Vec<OsString> {
+                $def_fn().prepare_project_args(args, target)
+            }
+
+            fn merge_default_args(
+                &self,
+                default_args: &[&OsStr],
+                args: &ArgMatches,
+            ) -> Vec<OsString> {
+                $def_fn().merge_default_args(default_args, args)
+            }
+
+            fn missing_required_args(
+                &self,
+                args: &ArgMatches,
+                target: ProjectTarget,
+            ) -> Vec<&'static str> {
+                $def_fn().missing_required_args(args, target)
             }
         }
     };
@@ -145,7 +162,7 @@ mod tests {
     fn it_prepares_build_args_correctly() {
         let cmd = get_command_by_name("build").unwrap();
         let matches = cmd.cli().get_matches_from(vec!["build", "--no-cache", "myproject"]);
-        let args = cmd.prepare(&matches);
+        let args = cmd.prepare(&matches, ProjectTarget::default());
 
         assert_eq!(args[0], OsString::from("build"));
         assert_eq!(args[1], OsString::from("--no-cache"));
@@ -157,7 +174,7 @@ mod tests {
         let matches = cmd.cli().get_matches_from(vec![
             "up", "-d", "--pull", "always", "myproject"
         ]);
-        let args = cmd.prepare(&matches);
+        let args = cmd.prepare(&matches, ProjectTarget::default());
 
         assert!(args.contains(&OsString::from("up")));
         assert!(args.contains(&OsString::from("--detach")));
@@ -0,0 +1,76 @@
+use clap::Command;
+use anyhow::Result;
+use schemars::{schema_for, Schema};
+
+use crate::parser::config::ConfigLayer;
+
+pub fn schema_config() -> Command {
+    Command::new("schema")
+        .about("Print the JSON Schema of the dctl config file, for editors to complete and check it")
+}
+
+pub fn exec_schema_config() -> Result<()> {
+    println!("{}", serde_json::to_string_pretty(&get_config_schema())?);
+
+    Ok(())
+}
+
+/// JSON Schema of a config file, generated from the type of a layer as any value
+/// may be set by another layer
+fn get_config_schema() -> Schema {
+    let mut schema = schema_for!(ConfigLayer);
+    schema.insert(String::from("title"), "dctl config".into());
+
+    schema
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::Value;
+
+    use super::*;
+
+    #[test]
+    fn it_describes_the_config_file() {
+        let schema = get_config_schema().to_value();
+
+        assert_eq!(schema["title"], "dctl config");
+        let properties = &schema["properties"];
+        assert!(properties["version"]["description"].is_string());
+        assert!(properties.get("origins").is_none());
+        assert_eq!(schema["additionalProperties"], false);
+        assert!(schema.get("required").is_none());
+        assert!(schema["$defs"]["MainLayer"].get("required").is_none());
+
+        let project = &schema["$defs"]["ComposeItem"]["properties"];
+        assert!(project["compose_files"]["description"].is_string());
+        assert!(project.get("status").is_none());
+        assert_eq!(project["enviroment_file"]["deprecated"], true);
+        assert_eq!(project["tags"]["deprecated"], true);
+    }
+
+    #[test]
+    fn it_refuses_the_unknown_keys() {
+        let schema = get_config_schema().to_value();
+
+        let definitions = ["ComposeItem", "MainLayer", "DefaultCommandArgs"];
+        for definition in definitions {
+            assert_eq!(schema["$defs"][definition]["additionalProperties"], false);
+        }
+        for definition in ["EnvFile", "EnvValue"] {
+            let variant = &schema["$defs"][definition]["anyOf"][1];
+            assert_eq!(variant["additionalProperties"], false);
+        }
+    }
+
+    #[test]
+    fn it_lists_the_command_names() {
+        let schema = get_config_schema().to_value();
+
+        let command_name = &schema["$defs"]["DefaultCommandArgs"]["properties"]["command_name"];
+        let names = command_name["enum"].as_array().unwrap();
+        assert!(names.contains(&Value::from("up")));
+        assert!(names.contains(&Value::from("logs")));
+        assert!(command_name["description"].is_string());
+    }
+}
@@ -0,0 +1,126 @@
+use clap::{Arg, ArgAction, ArgMatches, Command};
+use anyhow::Result;
+use toml::{Table, Value};
+
+use crate::parser::config::CliConfig;
+use crate::parser::origin::Origin;
+
+pub fn show_config() -> Command {
+    Command::new("show")
+        .about("Print the effective dctl configuration, once all the config files are merged")
+        .arg(
+            Arg::new("ORIGIN")
+                .long("origin")
+                .help("Print the file and line each value comes from")
+                .action(ArgAction::SetTrue),
+        )
+}
+
+pub fn exec_show_config(config: &dyn CliConfig, args: &ArgMatches) -> Result<()> {
+    let table = config.to_toml()?;
+
+    if args.get_flag("ORIGIN") {
+        print!("{}", format_with_origins(config, &table));
+    } else {
+        // A table is sorted by key, print the sections in the config file order
+        for key in ["version", "main", "collections"] {
+            if let Some(value) = table.get(key) {
+                let section = Table::from_iter([(key.to_string(), value.clone())]);
+                println!("{}", toml::to_string(&section)?);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Format the configuration as TOML, each value followed by a comment with its origin
+fn format_with_origins(config: &dyn CliConfig, table: &Table) -> String {
+    let mut lines: Vec<String> = vec![String::from("[main]")];
+
+    let main = table.get("main").and_then(Value::as_table);
+    for (key, value) in main.into_iter().flatten() {
+        if key != "default_command_args" {
+            let origin = config.get_origin(None, key);
+            lines.push(with_origin(format!("{} = {}", key, value), origin));
+        }
+    }
+
+    let default_command_args = main.and_then(|main| main.get("default_command_args"));
+    for args in default_command_args.and_then(Value::as_array).into_iter().flatten() {
+        let Some(args) = args.as_table() else {
+            continue;
+        };
+        let command_name = args.get("command_name").and_then(Value::as_str).unwrap_or_default();
+        let origin = config.get_origin(None, &format!("default_command_args.{}", command_name));
+
+        lines.push(String::new());
+        lines.push(with_origin(String::from("[[main.default_command_args]]"), origin));
+        for (key, value) in args {
+            lines.push(format!("{} = {}", key, value));
+        }
+    }
+
+    let collections = table.get("collections").and_then(Value::as_array);
+    for item in collections.into_iter().flatten() {
+        let Some(item) = item.as_table() else {
+            continue;
+        };
+        let alias = item.get("alias").and_then(Value::as_str);
+
+        lines.push(String::new());
+        lines.push(with_origin(
+            String::from("[[collections]]"),
+            alias.and_then(|alias| config.get_origin(Some(alias), "alias")),
+        ));
+        for (key, value) in item {
+            let origin = alias.and_then(|alias| config.get_origin(Some(alias), key));
+            lines.push(with_origin(format!("{} = {}", key, value), origin));
+        }
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+fn with_origin(line: String, origin: Option<Origin>) -> String {
+    match origin {
+        Some(origin) => format!("{}  # {}", line, origin),
+        None => line,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::parser::config::DctlConfig;
+
+    #[test]
+    fn it_formats_the_config_with_the_origin_of_each_value() {
+        let files = vec![
+            PathBuf::from("tests/layers/team.toml"),
+            PathBuf::from("tests/layers/user.toml"),
+        ];
+        let config = DctlConfig::load_layers(&files).unwrap();
+        let table = config.to_toml().unwrap();
+
+        let expected = r#"[main]
+docker_bin = "/usr/local/bin/docker"  # tests/layers/user.toml:2
+
+[[main.default_command_args]]  # tests/layers/team.toml:3
+command_args = ["-d", "--build"]
+command_name = "up"
+
+[[collections]]  # tests/layers/team.toml:7
+alias = "shop"  # tests/layers/team.toml:7
+compose_files = ["/srv/shop/docker-compose.yml"]  # tests/layers/team.toml:8
+
+[[collections]]  # tests/layers/user.toml:5
+alias = "web"  # tests/layers/user.toml:5
+compose_files = ["/home/test/web/docker-compose.yml"]  # tests/layers/user.toml:6
+"#;
+        assert_eq!(format_with_origins(&config, &table), expected);
+    }
+}
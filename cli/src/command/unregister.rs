@@ -1,11 +1,16 @@
 use std::env;
 use std::fs;
+use std::path::Path;
 
 use clap::{Arg, ArgMatches, Command};
 use anyhow::{anyhow, Context, Result};
 use toml_edit::DocumentMut;
 
 use crate::parser::config::CliConfig;
+use crate::utils::diff::unified_diff;
+use crate::utils::dry_run::is_dry_run;
+use crate::utils::error::DctlError;
+use crate::utils::suggest::closest_names;
 
 pub fn unregister_project() -> Command {
     Command::new("unregister")
@@ -40,11 +45,31 @@ pub fn exec_unregister_project(config: &dyn CliConfig, args: &ArgMatches) -> Res
 
     // Check if alias exists
     if config.get_compose_item_by_alias(alias.clone()).is_none() {
-        return Err(anyhow!("Project with alias '{}' does not exist", alias));
+        let aliases: Vec<String> =
+            config.get_all_compose_items().into_iter().map(|item| item.alias).collect();
+        return Err(DctlError::UnknownAlias {
+            alias: alias.to_string(),
+            suggestions: closest_names(alias, aliases.iter().map(String::as_str)),
+        }
+        .into());
     }
 
-    // Confirmation (skip if --force)
-    if !force {
+    // Only the projects of the user config file can be removed, the others are declared
+    // in the system or team config file, an included file or a `.dctl.toml` file
+    let config_path = expand_path(&get_config_path());
+    if let Some(origin) = config.get_origin(Some(alias), "alias") {
+        if origin.file != Path::new(&config_path) {
+            return Err(DctlError::Config(format!(
+                "project '{}' is declared in {}, remove it from this file to unregister it",
+                alias, origin
+            ))
+            .into());
+        }
+    }
+
+    // Confirmation (skip if --force, nothing is written in dry-run mode)
+    let dry_run = is_dry_run(args);
+    if !force && !dry_run {
         println!("Are you sure you want to unregister project '{}'? (y/N)", alias);
         let mut input = String::new();
         std::io::stdin()
@@ -58,7 +83,6 @@ pub fn exec_unregister_project(config: &dyn CliConfig, args: &ArgMatches) -> Res
     }
 
     // Read and modify config file
-    let config_path = expand_path(&get_config_path());
     let config_content = fs::read_to_string(&config_path)
         .context(format!("Failed to read config file: {}", config_path))?;
 
@@ -97,6 +121,13 @@ pub fn exec_unregister_project(config: &dyn CliConfig, args: &ArgMatches) -> Res
         return Err(anyhow!("Invalid config format: missing 'collections' section"));
     }
 
+    // Print the change instead of writing it in dry-run mode
+    if dry_run {
+        print!("{}", unified_diff(&config_content, &doc.to_string(), &config_path));
+        println!("Project '{}' would be unregistered", alias);
+        return Ok(());
+    }
+
     // Write back to file
     fs::write(&config_path, doc.to_string())
         .context(format!("Failed to write config file: {}", config_path))?;
@@ -110,10 +141,13 @@ pub fn exec_unregister_project(config: &dyn CliConfig, args: &ArgMatches) -> Res
 mod tests {
     use super::*;
     use crate::parser::config::{ComposeItem, DefaultCommandArgs};
+    use crate::parser::origin::Origin;
+    use crate::utils::engine::Engine;
 
     // Mock CliConfig for testing
     struct MockConfig {
         existing_aliases: Vec<String>,
+        origin: Option<Origin>,
     }
 
     impl CliConfig for MockConfig {
@@ -121,25 +155,42 @@ mod tests {
             Ok("/usr/bin/docker".to_string())
         }
 
+        fn get_engine(&self) -> Engine {
+            Engine::Docker
+        }
+
         fn get_compose_item_by_alias(&self, alias: String) -> Option<ComposeItem> {
             if self.existing_aliases.contains(&alias) {
                 Some(ComposeItem {
                     alias,
                     description: None,
                     compose_files: vec![],
-                    enviroment_file: None,
+                    environment_file: None,
                     use_project_name: None,
                     status: None,
+                    ..Default::default()
                 })
             } else {
                 None
             }
         }
 
+        fn get_compose_items_by_group(&self, _group: String) -> Vec<ComposeItem> {
+            vec![]
+        }
+
         fn get_all_compose_items(&self) -> Vec<ComposeItem> {
             vec![]
         }
 
+        fn to_toml(&self) -> Result<toml::Table> {
+            Ok(toml::Table::new())
+        }
+
+        fn get_origin(&self, _alias: Option<&str>, _key: &str) -> Option<Origin> {
+            self.origin.clone()
+        }
+
         fn get_default_command_args(&self, _command_name: &str) -> Option<DefaultCommandArgs> {
             None
         }
@@ -147,6 +198,7 @@ mod tests {
         fn load(_config_path_file: String) -> Result<Self> {
             Ok(MockConfig {
                 existing_aliases: vec![],
+                origin: None,
             })
         }
     }
@@ -230,6 +282,7 @@ mod tests {
     fn test_exec_unregister_alias_not_found() {
         let config = MockConfig {
             existing_aliases: vec![],
+            origin: None,
         };
 
         let cmd = unregister_project();
@@ -242,7 +295,29 @@ mod tests {
         assert!(result
             .unwrap_err()
             .to_string()
-            .contains("does not exist"));
+            .contains("No project found with alias"));
+    }
+
+    #[test]
+    fn test_exec_unregister_project_of_another_config_file() {
+        let config = MockConfig {
+            existing_aliases: vec![String::from("shop")],
+            origin: Some(Origin {
+                file: "/etc/dctl/config.toml".into(),
+                line: 3,
+            }),
+        };
+
+        let matches = unregister_project()
+            .try_get_matches_from(vec!["unregister", "shop", "--force"])
+            .unwrap();
+
+        let error = exec_unregister_project(&config, &matches).unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "project 'shop' is declared in /etc/dctl/config.toml:3, \
+            remove it from this file to unregister it"
+        );
     }
 
     #[test]
@@ -11,7 +11,7 @@ pub mod parser;
 pub mod utils;
 
 use parser::config::{CliConfig, DctlConfig};
-use utils::docker::{Container, Docker};
+use utils::error::report_error;
 
 fn load_config_path() -> Result<String> {
     let config_file_path = env::var("DCTL_CONFIG_FILE_PATH")
@@ -24,25 +24,30 @@ async fn main() {
     // Load .env file
     dotenv().ok();
 
+    // Run the commands working without the config
+    let matches = cli::cli().get_matches();
+    if let Some(result) = cli::run_without_config(&matches) {
+        if let Err(err) = result {
+            std::process::exit(report_error(&err));
+        }
+        return;
+    }
+
     // Get the custom config file path from env
     let config_file_path = load_config_path().unwrap();
 
     // Get config file
     let mut config: DctlConfig = match CliConfig::load(config_file_path) {
         Ok(config) => config,
-        Err(err) => {
-            eprintln!("Load config error: {}", err);
-            std::process::exit(1);
-        }
+        Err(err) => std::process::exit(report_error(&err)),
     };
 
-    // Get container manager
-    let docker: Docker = Container::init(config.get_container_bin_path().unwrap());
+    // Get container manager of the configured engine
+    let container = config.get_engine().container(config.get_container_bin_path().unwrap());
 
     // Execute cli command
-    if let Err(err) = cli::run(&docker, &mut config).await {
-        eprintln!("Command execution error: {}", err);
-        std::process::exit(1);
+    if let Err(err) = cli::run(container.as_ref(), &mut config, &matches).await {
+        std::process::exit(report_error(&err));
     }
 }
 
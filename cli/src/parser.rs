@@ -1,4 +1,7 @@
 pub mod config;
+pub mod migrate;
+pub mod origin;
+pub mod validate;
 
 #[cfg(test)]
 mod tests;
@@ -1,8 +1,27 @@
-use anyhow::{Context, Result};
-use serde::Deserialize;
-use std::{ffi::OsStr, fs};
+use anyhow::Result;
+use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Component, Path, PathBuf};
+use std::{env, ffi::OsStr, fs};
 use tabled::Tabled;
 
+use crate::command::registry::get_compose_commands;
+use crate::parser::migrate::{get_deprecations, CONFIG_VERSION};
+use crate::parser::origin::{ConfigOrigins, Origin};
+use crate::parser::validate::{parse_error, validate_config_file, ConfigFileKind};
+use crate::utils::engine::Engine;
+use crate::utils::error::DctlError;
+use crate::utils::suggest::closest_names;
+
+/// Name of the project config file, versioned next to the compose files
+pub const PROJECT_CONFIG_FILE: &str = ".dctl.toml";
+/// Config file shared by all the users of the system
+pub const SYSTEM_CONFIG_FILE: &str = "/etc/dctl/config.toml";
+/// Environment variable of the team config file path
+pub const TEAM_CONFIG_ENV: &str = "DCTL_TEAM_CONFIG_FILE_PATH";
+
 #[derive(Debug, Clone, Deserialize, Tabled, PartialEq, Eq)]
 pub enum ComposeStatus {
     Running,
@@ -11,54 +30,342 @@ pub enum ComposeStatus {
     ConfigError,
 }
 
-#[derive(Debug, Clone, Deserialize, Tabled, PartialEq)]
+/// A docker compose project
+#[derive(Debug, Clone, Default, Deserialize, Serialize, Tabled, PartialEq, JsonSchema)]
+#[schemars(deny_unknown_fields, transform = add_deprecated_names)]
 pub struct ComposeItem {
+    /// Unique name of the project, given to the dctl commands
     #[tabled(rename = " 🐋 Alias", display  = "display_alias")]
     pub alias: String,
+    /// Free text description
     #[tabled(rename = " 📃 Description", display  = "display_description")]
     pub description: Option<String>,
     #[tabled(rename = "⚡Status", display  = "display_status")]
+    #[serde(skip_serializing)]
+    #[schemars(skip)]
     pub status: Option<ComposeStatus>,
+    /// Use the alias as the docker compose project name, true by default
     #[tabled(skip)]
     pub use_project_name: Option<bool>,
+    /// Path of the environment file, `enviroment_file` being its deprecated name
     #[tabled(skip)]
-    pub enviroment_file: Option<String>,
+    #[serde(alias = "enviroment_file")]
+    pub environment_file: Option<String>,
+    /// Environment files applied in order after `environment_file`, a later one
+    /// overriding the values of the previous ones
+    #[tabled(skip)]
+    pub env_files: Option<Vec<EnvFile>>,
+    /// Paths of the compose files, relative to the config file directory
     #[tabled(skip)]
     pub compose_files: Vec<String>,
+    /// Groups the project belongs to, `tags` being an alias
+    #[tabled(skip)]
+    #[serde(alias = "tags")]
+    pub groups: Option<Vec<String>>,
+    /// Compose profiles enabled for the commands of the project
+    #[tabled(skip)]
+    pub profiles: Option<Vec<String>>,
+    /// Default args of the commands of the project
+    #[tabled(skip)]
+    pub default_command_args: Option<Vec<DefaultCommandArgs>>,
+    /// Engine of the project, when it's not the main one
+    #[tabled(skip)]
+    pub engine: Option<Engine>,
+    /// Path of the binary of the project engine, instead of the main or default one
+    #[tabled(skip)]
+    pub docker_bin: Option<String>,
+    /// Docker context of the daemon running the project, set as `DOCKER_CONTEXT`, or
+    /// as `CONTAINER_CONNECTION` for podman
+    #[tabled(skip)]
+    pub context: Option<String>,
+    /// Address of the daemon running the project, set as `DOCKER_HOST`
+    #[tabled(rename = " 🎯 Endpoint", display("display_endpoint", &self.context))]
+    pub docker_host: Option<String>,
+    /// Environment variables of the compose commands of the project
+    #[tabled(skip)]
+    pub env: Option<BTreeMap<String, EnvValue>>,
+}
+
+/// Environment file of a project
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
+#[serde(untagged)]
+#[schemars(deny_unknown_fields)]
+pub enum EnvFile {
+    /// Path of a required file
+    Path(String),
+    File {
+        /// Path of the file, relative to the config file directory
+        path: String,
+        /// A missing optional file is skipped, true by default
+        required: Option<bool>,
+    },
+}
+
+impl EnvFile {
+    pub fn path(&self) -> &str {
+        match self {
+            EnvFile::Path(path) | EnvFile::File { path, .. } => path,
+        }
+    }
+
+    pub fn is_required(&self) -> bool {
+        match self {
+            EnvFile::Path(_) => true,
+            EnvFile::File { required, .. } => required.unwrap_or(true),
+        }
+    }
+
+    fn path_mut(&mut self) -> &mut String {
+        match self {
+            EnvFile::Path(path) | EnvFile::File { path, .. } => path,
+        }
+    }
+}
+
+/// Value of an environment variable of a project
+#[derive(Debug, Clone, Deserialize, Serialize, PartialEq, JsonSchema)]
+#[serde(untagged)]
+#[schemars(deny_unknown_fields)]
+pub enum EnvValue {
+    /// The value itself
+    Value(String),
+    /// A secret, read from the output of a shell command when the project runs
+    Secret {
+        /// Shell command printing the secret, like `pass show shop/db`
+        command: String,
+    },
 }
 
 pub trait CliConfig {
     fn get_container_bin_path(&self) -> Result<String>;
+    /// Engine running the compose commands of the projects
+    fn get_engine(&self) -> Engine;
     fn get_default_command_args(&self, command_name: &str) -> Option<DefaultCommandArgs>;
     fn load(config_path_file: String) -> Result<Self>
     where
         Self: Sized;
     fn get_compose_item_by_alias(&self, alias: String) -> Option<ComposeItem>;
+    fn get_compose_items_by_group(&self, group: String) -> Vec<ComposeItem>;
     fn get_all_compose_items(&self) -> Vec<ComposeItem>;
+    /// Effective configuration, as a TOML table
+    fn to_toml(&self) -> Result<toml::Table>;
+    /// File and line of a `[main]` value, or of a project field when an alias is given
+    fn get_origin(&self, alias: Option<&str>, key: &str) -> Option<Origin>;
+
+    /// Find the project whose compose file directory is the given directory or one of
+    /// its ancestors, the closest one when projects are nested
+    fn get_compose_item_by_dir(&self, dir: &Path) -> Option<ComposeItem> {
+        let mut result: Option<(usize, ComposeItem)> = None;
+        for item in self.get_all_compose_items() {
+            let Some(project_dir) = item.get_project_dir() else {
+                continue;
+            };
+            let depth = project_dir.components().count();
+            if dir.starts_with(&project_dir) && result.as_ref().is_none_or(|(d, _)| depth > *d) {
+                result = Some((depth, item));
+            }
+        }
+
+        result.map(|(_, item)| item)
+    }
+
+    /// Find a project by its alias, or by a prefix matching a single alias
+    fn resolve_compose_item(&self, name: &str) -> Result<ComposeItem> {
+        if let Some(item) = self.get_compose_item_by_alias(name.to_string()) {
+            return Ok(item);
+        }
+
+        let items = self.get_all_compose_items();
+        let mut candidates: Vec<&ComposeItem> =
+            items.iter().filter(|item| item.alias.starts_with(name)).collect();
+
+        match candidates.len() {
+            0 => Err(DctlError::UnknownAlias {
+                alias: name.to_string(),
+                suggestions: closest_names(name, items.iter().map(|item| item.alias.as_str())),
+            }
+            .into()),
+            1 => Ok(candidates.remove(0).clone()),
+            _ => {
+                let mut aliases: Vec<String> =
+                    candidates.iter().map(|item| item.alias.to_string()).collect();
+                aliases.sort();
+
+                Err(DctlError::AmbiguousAlias {
+                    alias: name.to_string(),
+                    candidates: aliases,
+                }
+                .into())
+            }
+        }
+    }
 }
 
-#[derive(Debug, Deserialize)]
+/// The `[main]` section, settings of all the projects
+#[derive(Debug, Deserialize, Serialize)]
 pub struct Config {
+    /// Path of the docker binary, or of the binary of the engine
     pub docker_bin: String,
+    /// Engine running the compose commands, docker by default
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub engine: Option<Engine>,
+    /// Default args of the commands of all the projects
     pub default_command_args: Option<Vec<DefaultCommandArgs>>,
+    /// Glob patterns of config files holding more projects
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub include: Option<Vec<String>>,
 }
 
-#[derive(Debug, Deserialize, Clone, PartialEq)]
+/// Args added to a docker compose command
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq, JsonSchema)]
+#[schemars(deny_unknown_fields)]
 pub struct DefaultCommandArgs {
+    /// Name of the docker compose command
+    #[schemars(schema_with = "command_name_schema")]
     pub command_name: String,
+    /// Args of the command, merged with the ones of the command line
     pub command_args: Vec<String>,
+    /// For a project, extend the main default args instead of overriding them
+    pub extend: Option<bool>,
 }
 
-#[derive(Debug, Deserialize)]
+/// The dctl config, merged from all the config files
+#[derive(Debug, Deserialize, Serialize)]
 pub struct DctlConfig {
+    /// Version of the config format, 1 when not set
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<i64>,
+    /// Settings of all the projects
     pub main: Config,
+    /// The docker compose projects
     pub collections: Vec<ComposeItem>,
+    #[serde(skip)]
+    pub origins: ConfigOrigins,
+}
+
+/// A config file of a layer (system, team or user), a higher layer overrides
+/// the values of the lower ones
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[serde(deny_unknown_fields)]
+pub(crate) struct ConfigLayer {
+    /// Version of the config format, 1 when not set
+    version: Option<i64>,
+    /// Settings of all the projects
+    #[serde(default)]
+    main: MainLayer,
+    /// The docker compose projects
+    #[serde(default)]
+    collections: Vec<ComposeItem>,
+}
+
+/// The `[main]` section, settings of all the projects
+#[derive(Debug, Default, Deserialize, JsonSchema)]
+#[schemars(deny_unknown_fields)]
+struct MainLayer {
+    /// Path of the docker binary, or of the binary of the engine, set by one of the layers
+    docker_bin: Option<String>,
+    /// Engine running the compose commands, docker by default
+    engine: Option<Engine>,
+    /// Default args of the commands of all the projects
+    default_command_args: Option<Vec<DefaultCommandArgs>>,
+    /// Glob patterns of config files holding more projects
+    include: Option<Vec<String>>,
+}
+
+impl ConfigLayer {
+    /// Override the values with the ones of a higher layer: the docker binary,
+    /// the default args of each command and the projects with the same alias
+    fn merge(&mut self, layer: ConfigLayer) {
+        if layer.version.is_some() {
+            self.version = layer.version;
+        }
+        if layer.main.docker_bin.is_some() {
+            self.main.docker_bin = layer.main.docker_bin;
+        }
+        if layer.main.engine.is_some() {
+            self.main.engine = layer.main.engine;
+        }
+
+        for command_args in layer.main.default_command_args.into_iter().flatten() {
+            let default_command_args = self.main.default_command_args.get_or_insert_default();
+            match default_command_args
+                .iter_mut()
+                .find(|args| args.command_name == command_args.command_name)
+            {
+                Some(existing) => *existing = command_args,
+                None => default_command_args.push(command_args),
+            }
+        }
+
+        for item in layer.collections {
+            match self.collections.iter_mut().find(|i| i.alias == item.alias) {
+                Some(existing) => *existing = item,
+                None => self.collections.push(item),
+            }
+        }
+    }
+
+    /// Expand the environment variables of the paths and args of the layer
+    fn expand_env_vars(&mut self) -> Result<(), String> {
+        if let Some(docker_bin) = &self.main.docker_bin {
+            self.main.docker_bin = Some(expand_env_vars(docker_bin)?);
+        }
+        for args in self.main.default_command_args.iter_mut().flatten() {
+            args.expand_env_vars()?;
+        }
+        for pattern in self.main.include.iter_mut().flatten() {
+            *pattern = expand_env_vars(pattern)?;
+        }
+        for item in &mut self.collections {
+            item.expand_env_vars()?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Projects of an included config file, or of a file of the conf.d directory
+#[derive(Debug, Deserialize)]
+struct IncludedConfig {
+    #[serde(default)]
+    collections: Vec<ComposeItem>,
+}
+
+/// Schema of a command name, one of the docker compose commands
+fn command_name_schema(_generator: &mut SchemaGenerator) -> Schema {
+    let names: Vec<&str> = get_compose_commands().iter().map(|handler| handler.name()).collect();
+    json_schema!({
+        "type": "string",
+        "enum": names,
+    })
+}
+
+/// Add the deprecated names of the project keys to its schema, still loaded
+fn add_deprecated_names(schema: &mut Schema) {
+    let Some(properties) = schema.get_mut("properties").and_then(|value| value.as_object_mut())
+    else {
+        return;
+    };
+    for (name, deprecated_name) in [("environment_file", "enviroment_file"), ("groups", "tags")] {
+        if let Some(mut property) = properties.get(name).cloned() {
+            property["deprecated"] = true.into();
+            properties.insert(deprecated_name.to_string(), property);
+        }
+    }
 }
 
 fn display_alias(alias: &String) -> String {
     alias.to_string()
 }
 
+fn display_endpoint(docker_host: &Option<String>, context: &Option<String>) -> String {
+    match (docker_host, context) {
+        (Some(docker_host), _) => docker_host.to_string(),
+        (None, Some(context)) => format!("context {}", context),
+        (None, None) => String::new(),
+    }
+}
+
 fn display_status(status: &Option<ComposeStatus>) -> String {
     match status {
         Some(s) => {
@@ -104,21 +411,139 @@ impl ComposeItem {
             item_args.push(OsStr::new(&compose_item.alias));
         }
 
-        match &compose_item.enviroment_file {
-            Some(env_file) => {
+        // A missing optional env file would make docker compose fail
+        for (env_file, required) in compose_item.get_env_files() {
+            if required || Path::new(shellexpand::tilde(env_file).as_ref()).exists() {
                 item_args.push(OsStr::new("--env-file"));
                 item_args.push(OsStr::new(env_file));
             }
-            None => {}
-        };
+        }
 
         compose_item.compose_files.iter().for_each(|compose_file| {
             item_args.push(OsStr::new("-f"));
             item_args.push(OsStr::new(compose_file));
         });
 
+        for profile in compose_item.profiles.iter().flatten() {
+            item_args.push(OsStr::new("--profile"));
+            item_args.push(OsStr::new(profile));
+        }
+
         item_args
     }
+
+    /// Default args of a command for this project, they override the main
+    /// default args of the same command, or extend them with `extend = true`
+    pub fn get_default_command_args(
+        &self,
+        command_name: &str,
+        main_command_args: Option<DefaultCommandArgs>,
+    ) -> Option<DefaultCommandArgs> {
+        let project_command_args = self
+            .default_command_args
+            .as_ref()
+            .and_then(|args| args.iter().find(|arg| arg.command_name == command_name))
+            .cloned();
+
+        match (main_command_args, project_command_args) {
+            (Some(mut main_args), Some(project_args)) if project_args.extend.unwrap_or(false) => {
+                main_args.command_args.extend(project_args.command_args);
+                Some(main_args)
+            }
+            (main_args, None) => main_args,
+            (_, project_args) => project_args,
+        }
+    }
+
+    /// Env files of the project in the order they apply, with whether they are required
+    pub fn get_env_files(&self) -> Vec<(&str, bool)> {
+        let env_files = self.env_files.iter().flatten();
+
+        self.environment_file
+            .iter()
+            .map(|env_file| (env_file.as_str(), true))
+            .chain(env_files.map(|env_file| (env_file.path(), env_file.is_required())))
+            .collect()
+    }
+
+    /// Environment variables of the compose commands of the project run by the engine,
+    /// `docker_host` and `context` overriding the same variables of `env`
+    pub fn to_env(&self, engine: Engine) -> Vec<(String, EnvValue)> {
+        let mut env = self.env.clone().unwrap_or_default();
+        if let Some(context) = &self.context {
+            env.insert(engine.context_var().to_string(), EnvValue::Value(context.to_string()));
+        }
+        if let Some(docker_host) = &self.docker_host {
+            env.insert(String::from("DOCKER_HOST"), EnvValue::Value(docker_host.to_string()));
+        }
+
+        env.into_iter().collect()
+    }
+
+    /// Expand the environment variables of the paths, endpoint and default args of the
+    /// project, the env values being passed as is, like a password holding a `$`
+    pub fn expand_env_vars(&mut self) -> Result<(), String> {
+        for file in &mut self.compose_files {
+            *file = expand_env_vars(file)?;
+        }
+        if let Some(file) = &self.environment_file {
+            self.environment_file = Some(expand_env_vars(file)?);
+        }
+        for env_file in self.env_files.iter_mut().flatten() {
+            *env_file.path_mut() = expand_env_vars(env_file.path())?;
+        }
+        if let Some(docker_bin) = &self.docker_bin {
+            self.docker_bin = Some(expand_env_vars(docker_bin)?);
+        }
+        if let Some(context) = &self.context {
+            self.context = Some(expand_env_vars(context)?);
+        }
+        if let Some(docker_host) = &self.docker_host {
+            self.docker_host = Some(expand_env_vars(docker_host)?);
+        }
+        for args in self.default_command_args.iter_mut().flatten() {
+            args.expand_env_vars()?;
+        }
+
+        Ok(())
+    }
+
+    /// Make the relative paths of the project absolute, from the given directory
+    pub fn resolve_paths(&mut self, base_dir: &Path) {
+        let resolve = |path: &String| -> String {
+            if path.starts_with('~') || Path::new(path).is_absolute() {
+                return path.to_string();
+            }
+            let path = base_dir.join(path);
+            normalize_path(&std::path::absolute(&path).unwrap_or(path))
+                .to_string_lossy()
+                .to_string()
+        };
+
+        self.compose_files = self.compose_files.iter().map(resolve).collect();
+        self.environment_file = self.environment_file.as_ref().map(resolve);
+        for env_file in self.env_files.iter_mut().flatten() {
+            *env_file.path_mut() = resolve(&env_file.path().to_string());
+        }
+    }
+
+    /// Directory of the first compose file, as `dctl cd` does
+    pub fn get_project_dir(&self) -> Option<PathBuf> {
+        let first_file = PathBuf::from(shellexpand::tilde(self.compose_files.first()?).as_ref());
+        let dir = first_file.parent()?;
+        if !dir.is_absolute() {
+            return None;
+        }
+
+        Some(dir.canonicalize().unwrap_or(dir.to_path_buf()))
+    }
+
+    pub fn in_group(&self, group: &str) -> bool {
+        match &self.groups {
+            Some(groups) => groups.iter().any(|g| g == group),
+            None => false,
+        }
+    }
 }
 
 impl DefaultCommandArgs {
@@ -126,6 +551,7 @@ impl DefaultCommandArgs {
         DefaultCommandArgs {
             command_name: command_name.to_string(),
             command_args: Vec::new(),
+            extend: None,
         }
     }
 
@@ -138,26 +564,265 @@ impl DefaultCommandArgs {
         }
         default_arg
     }
+
+    fn expand_env_vars(&mut self) -> Result<(), String> {
+        for arg in &mut self.command_args {
+            *arg = expand_env_vars(arg)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Expand `~`, `${VAR}` and `${VAR:-default}` in a config value, the error being
+/// the name of the unset variable. A bare `$` is kept, and `$${` is a literal `${`
+fn expand_env_vars(value: &str) -> Result<String, String> {
+    let value = shellexpand::tilde(value);
+    let mut expanded = String::with_capacity(value.len());
+    let mut rest = value.as_ref();
+    while let Some(start) = rest.find("${") {
+        if rest[..start].ends_with('$') {
+            expanded.push_str(&rest[..start - 1]);
+            expanded.push_str("${");
+            rest = &rest[start + 2..];
+            continue;
+        }
+        let Some(end) = rest[start..].find('}').map(|end| start + end) else {
+            break;
+        };
+        expanded.push_str(&rest[..start]);
+        let (name, default) = match rest[start + 2..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&rest[start + 2..end], None),
+        };
+        match (env::var(name), default) {
+            (Ok(var), Some(default)) if var.is_empty() => expanded.push_str(default),
+            (Ok(var), _) => expanded.push_str(&var),
+            (Err(_), Some(default)) => expanded.push_str(default),
+            (Err(_), None) => return Err(name.to_string()),
+        }
+        rest = &rest[end + 1..];
+    }
+    expanded.push_str(rest);
+
+    Ok(expanded)
+}
+
+/// Validate and deserialize a config file, printing its warnings: the unknown keys,
+/// and the deprecated content still loaded as before
+fn parse_config_file<T: DeserializeOwned>(
+    content: &str,
+    path: &Path,
+    kind: ConfigFileKind,
+) -> Result<T> {
+    for warning in validate_config_file(content, path, kind)? {
+        eprintln!("warning: {}", warning);
+    }
+    let deprecations = get_deprecations(content);
+    if !deprecations.is_empty() {
+        eprintln!(
+            "warning: {} uses a deprecated format ({}), run `dctl config migrate` to update it",
+            path.display(),
+            deprecations.join(", ")
+        );
+    }
+
+    Ok(toml::from_str(content).map_err(|err| parse_error(content, path, &err))?)
+}
+
+/// Error of a variable used in a config file, without value nor default
+fn unset_env_var_error(var_name: &str, path: &Path) -> DctlError {
+    DctlError::Config(format!(
+        "environment variable {var_name} is not set, used in {}",
+        path.display()
+    ))
+}
+
+/// Remove the `.` and `..` components of a path, without reading the file system
+pub fn normalize_path(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            _ => normalized.push(component),
+        }
+    }
+
+    normalized
 }
 
 impl DctlConfig {
-    fn load_config_file(config_path_file: String) -> Result<String> {
-        // Load config file
-        let full_config_path = shellexpand::tilde(&config_path_file).to_string();
+    /// Find the closest project config file, from the directory up to the root
+    pub fn find_project_config_file(dir: &Path) -> Option<PathBuf> {
+        dir.ancestors()
+            .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+            .find(|path| path.is_file())
+    }
 
-        // Read the config file
-        let config_content = fs::read_to_string(&full_config_path)
-            .context(format!("config file not found in {full_config_path}"))?;
+    /// Load a project config file, its paths are relative to its directory
+    pub fn load_project_config_file(path: &Path) -> Result<(ComposeItem, ConfigOrigins)> {
+        let content = fs::read_to_string(path).map_err(|err| {
+            DctlError::Config(format!("can't read {}: {err}", path.display()))
+        })?;
+        let mut item: ComposeItem = parse_config_file(&content, path, ConfigFileKind::Project)?;
+        item.expand_env_vars().map_err(|var_name| unset_env_var_error(&var_name, path))?;
+        if let Some(dir) = path.parent() {
+            item.resolve_paths(dir);
+        }
 
-        Ok(config_content)
+        Ok((item, ConfigOrigins::from_project_file(&content, path)))
     }
 
-    fn parse_config_file(config_content: String) -> Result<Self> {
-        // Parse the config file
-        let config: DctlConfig = toml::from_str(config_content.as_str())
-            .context("TOML parse error, check your config file structure.")?;
+    /// Add a project, it replaces the project with the same alias
+    pub fn merge_compose_item(&mut self, item: ComposeItem) {
+        match self.collections.iter_mut().find(|i| i.alias == item.alias) {
+            Some(existing) => *existing = item,
+            None => self.collections.push(item),
+        }
+    }
 
-        Ok(config)
+    /// Config files of the layers, from the lowest priority: the system file, the team
+    /// file, then the user file, which is optional when another layer exists
+    pub fn get_layer_files(user_config_path: &Path) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = Vec::new();
+        if Path::new(SYSTEM_CONFIG_FILE).is_file() {
+            files.push(PathBuf::from(SYSTEM_CONFIG_FILE));
+        }
+        if let Ok(team_config_path) = env::var(TEAM_CONFIG_ENV) {
+            files.push(PathBuf::from(shellexpand::tilde(&team_config_path).as_ref()));
+        }
+        if files.is_empty() || user_config_path.is_file() {
+            files.push(user_config_path.to_path_buf());
+        }
+
+        files
+    }
+
+    /// Load the config files of the layers, each layer overrides the lower ones
+    pub fn load_layers(files: &[PathBuf]) -> Result<Self> {
+        let mut config = ConfigLayer::default();
+        let mut origins = ConfigOrigins::default();
+        for file in files {
+            let (layer, layer_origins) = DctlConfig::load_layer(file)?;
+            config.merge(layer);
+            origins.merge(layer_origins);
+        }
+
+        let docker_bin = config.main.docker_bin.ok_or_else(|| {
+            DctlError::Config(String::from("docker_bin is not set in the [main] section"))
+        })?;
+
+        Ok(DctlConfig {
+            version: config.version,
+            main: Config {
+                docker_bin,
+                engine: config.main.engine,
+                default_command_args: config.main.default_command_args,
+                include: None,
+            },
+            collections: config.collections,
+            origins,
+        })
+    }
+
+    /// Load the config file of a layer, with its conf.d and included files
+    fn load_layer(path: &Path) -> Result<(ConfigLayer, ConfigOrigins)> {
+        let content = fs::read_to_string(path).map_err(|_| {
+            DctlError::Config(format!("config file not found in {}", path.display()))
+        })?;
+        let mut layer: ConfigLayer = parse_config_file(&content, path, ConfigFileKind::Layer)?;
+        if let Some(version) = layer.version.filter(|version| *version > CONFIG_VERSION) {
+            return Err(DctlError::Config(format!(
+                "{} has the config version {version}, this dctl supports up to {CONFIG_VERSION}",
+                path.display()
+            ))
+            .into());
+        }
+        layer.expand_env_vars().map_err(|var_name| unset_env_var_error(&var_name, path))?;
+        let mut origins = ConfigOrigins::from_config_file(&content, path);
+
+        let config_dir = path.parent().unwrap_or(Path::new("."));
+        for item in &mut layer.collections {
+            item.resolve_paths(config_dir);
+        }
+        let include = layer.main.include.take().unwrap_or_default();
+        let included_files = DctlConfig::get_included_files(&include, config_dir)?;
+        DctlConfig::merge_included_files(&mut layer, &mut origins, path, &included_files)?;
+
+        Ok((layer, origins))
+    }
+
+    /// Config files of the conf.d directory, next to the main config file, then
+    /// the ones matching the include patterns, each pattern matches being sorted
+    fn get_included_files(include: &[String], config_dir: &Path) -> Result<Vec<PathBuf>> {
+        let mut patterns = vec![config_dir.join("conf.d").join("*.toml")];
+        for pattern in include {
+            patterns.push(config_dir.join(pattern));
+        }
+
+        let mut files: Vec<PathBuf> = Vec::new();
+        for pattern in patterns {
+            let pattern = pattern.to_string_lossy();
+            let mut matches: Vec<PathBuf> = glob::glob(&pattern)
+                .map_err(|err| DctlError::Config(format!("invalid include {pattern}: {err}")))?
+                .filter_map(|path| path.ok())
+                .collect();
+            matches.sort();
+
+            for path in matches {
+                if !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+        }
+
+        Ok(files)
+    }
+
+    /// Add the projects of the included config files to the layer, an alias
+    /// can't be declared twice
+    fn merge_included_files(
+        layer: &mut ConfigLayer,
+        origins: &mut ConfigOrigins,
+        config_path: &Path,
+        files: &[PathBuf],
+    ) -> Result<()> {
+        let mut sources: Vec<(String, PathBuf)> = layer
+            .collections
+            .iter()
+            .map(|item| (item.alias.to_string(), config_path.to_path_buf()))
+            .collect();
+
+        for path in files {
+            let content = fs::read_to_string(path).map_err(|err| {
+                DctlError::Config(format!("can't read {}: {err}", path.display()))
+            })?;
+            let included: IncludedConfig =
+                parse_config_file(&content, path, ConfigFileKind::Included)?;
+
+            for mut item in included.collections {
+                item.expand_env_vars()
+                    .map_err(|var_name| unset_env_var_error(&var_name, path))?;
+                item.resolve_paths(path.parent().unwrap_or(Path::new(".")));
+                if let Some((_, source)) = sources.iter().find(|(alias, _)| *alias == item.alias) {
+                    return Err(DctlError::Config(format!(
+                        "alias {} is declared in {} and {}",
+                        item.alias,
+                        source.display(),
+                        path.display()
+                    ))
+                    .into());
+                }
+                sources.push((item.alias.to_string(), path.to_path_buf()));
+                layer.collections.push(item);
+            }
+            origins.merge(ConfigOrigins::from_config_file(&content, path));
+        }
+
+        Ok(())
     }
 }
 
@@ -166,6 +831,10 @@ impl CliConfig for DctlConfig {
         Ok(self.main.docker_bin.to_string())
     }
 
+    fn get_engine(&self) -> Engine {
+        self.main.engine.unwrap_or_default()
+    }
+
     fn get_default_command_args(&self, command_name: &str) -> Option<DefaultCommandArgs> {
         let mut result: Option<DefaultCommandArgs> = None;
         if let Some(default_command_args) = &self.main.default_command_args {
@@ -181,10 +850,19 @@ impl CliConfig for DctlConfig {
     }
 
     fn load(config_path_file: String) -> Result<Self> {
-        // Read the config file
-        let config_content = DctlConfig::load_config_file(config_path_file)?;
-        // Parse the config file
-        let config: DctlConfig = DctlConfig::parse_config_file(config_content)?;
+        // Load the system, team and user config files
+        let user_config_path = PathBuf::from(shellexpand::tilde(&config_path_file).as_ref());
+        let mut config = DctlConfig::load_layers(&DctlConfig::get_layer_files(&user_config_path))?;
+
+        // Merge the project config file of the current directory
+        let project_config_file = env::current_dir()
+            .ok()
+            .and_then(|dir| DctlConfig::find_project_config_file(&dir));
+        if let Some(path) = project_config_file {
+            let (item, origins) = DctlConfig::load_project_config_file(&path)?;
+            config.merge_compose_item(item);
+            config.origins.merge(origins);
+        }
 
         Ok(config)
     }
@@ -201,9 +879,27 @@ impl CliConfig for DctlConfig {
         result
     }
 
+    fn get_compose_items_by_group(&self, group: String) -> Vec<ComposeItem> {
+        self.get_all_compose_items()
+            .into_iter()
+            .filter(|item| item.in_group(&group))
+            .collect()
+    }
+
     fn get_all_compose_items(&self) -> Vec<ComposeItem> {
         self.collections.clone()
     }
+
+    fn to_toml(&self) -> Result<toml::Table> {
+        Ok(toml::Table::try_from(self)?)
+    }
+
+    fn get_origin(&self, alias: Option<&str>, key: &str) -> Option<Origin> {
+        match alias {
+            Some(alias) => self.origins.get_project(alias, key).cloned(),
+            None => self.origins.get_main(key).cloned(),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -243,14 +939,45 @@ mod tests {
         assert_eq!(display_status(&status), "🔴 Stopped");
     }
 
+    #[test]
+    fn it_displays_the_project_endpoint() {
+        let docker_host = Some(String::from("ssh://ci@build-vm"));
+        let context = Some(String::from("build-vm"));
+        assert_eq!(display_endpoint(&docker_host, &None), "ssh://ci@build-vm");
+        assert_eq!(display_endpoint(&None, &context), "context build-vm");
+        assert_eq!(display_endpoint(&None, &None), "");
+    }
+
+    #[test]
+    fn it_expands_only_the_braced_environment_variables() {
+        env::set_var("DCTL_TEST_EXPAND_DIR", "/work");
+        env::set_var("DCTL_TEST_EXPAND_EMPTY", "");
+        assert_eq!(expand_env_vars("${DCTL_TEST_EXPAND_DIR}/shop").unwrap(), "/work/shop");
+        assert_eq!(expand_env_vars("${DCTL_TEST_EXPAND_EMPTY:-.env}").unwrap(), ".env");
+        assert_eq!(expand_env_vars("${DCTL_TEST_EXPAND_UNSET:-.env}").unwrap(), ".env");
+        let bare = "pa$sw0rd $DCTL_TEST_EXPAND_DIR";
+        assert_eq!(expand_env_vars(bare).unwrap(), bare);
+        let escaped = "$${DCTL_TEST_EXPAND_DIR} ${";
+        assert_eq!(expand_env_vars(escaped).unwrap(), "${DCTL_TEST_EXPAND_DIR} ${");
+        assert!(!expand_env_vars("~/shop").unwrap().starts_with('~'));
+        assert_eq!(
+            expand_env_vars("${DCTL_TEST_EXPAND_UNSET}").unwrap_err(),
+            "DCTL_TEST_EXPAND_UNSET"
+        );
+    }
+
     #[test]
     fn it_returns_the_docker_bin_path() {
         let config = DctlConfig {
+            version: None,
             main: Config {
                 docker_bin: String::from("/usr/bin/docker"),
+                engine: None,
                 default_command_args: None,
+                include: None,
             },
             collections: Vec::new(),
+            origins: ConfigOrigins::default(),
         };
 
         assert_eq!(config.get_container_bin_path().unwrap(), "/usr/bin/docker");
@@ -266,8 +993,9 @@ mod tests {
                 String::from("docker-compose.yml"),
                 String::from("docker-compose.override.yml"),
             ],
-            enviroment_file: Some(String::from("test.env")),
+            environment_file: Some(String::from("test.env")),
             status: None,
+            ..Default::default()
         };
 
         compose_item.set_status(0, 0);
@@ -296,19 +1024,67 @@ mod tests {
                 String::from("docker-compose.yml"),
                 String::from("docker-compose.override.yml"),
             ],
-            enviroment_file: Some(String::from("test.env")),
+            environment_file: Some(String::from("test.env")),
             status: None,
+            profiles: Some(vec![String::from("debug"), String::from("tools")]),
+            ..Default::default()
         };
 
         let args = ComposeItem::to_args(&compose_item);
 
-        assert!(args.len() == 6);
+        assert!(args.len() == 10);
         assert!(args[0] == OsStr::new("--env-file"));
         assert!(args[1] == OsStr::new("test.env"));
         assert!(args[2] == OsStr::new("-f"));
         assert!(args[3] == OsStr::new("docker-compose.yml"));
         assert!(args[4] == OsStr::new("-f"));
         assert!(args[5] == OsStr::new("docker-compose.override.yml"));
+        assert!(args[6] == OsStr::new("--profile"));
+        assert!(args[7] == OsStr::new("debug"));
+        assert!(args[8] == OsStr::new("--profile"));
+        assert!(args[9] == OsStr::new("tools"));
+    }
+
+    #[test]
+    fn it_returns_the_env_files_in_order_skipping_the_missing_optional_ones() {
+        let compose_item = ComposeItem {
+            alias: String::from("test"),
+            use_project_name: Some(false),
+            environment_file: Some(String::from("test.env")),
+            env_files: Some(vec![
+                EnvFile::Path(String::from(".env")),
+                EnvFile::File {
+                    path: String::from("tests/.env.local"),
+                    required: Some(false),
+                },
+                EnvFile::File {
+                    path: String::from("/nonexistent/.env.local"),
+                    required: Some(false),
+                },
+            ]),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compose_item.get_env_files(),
+            vec![
+                ("test.env", true),
+                (".env", true),
+                ("tests/.env.local", false),
+                ("/nonexistent/.env.local", false),
+            ]
+        );
+        assert_eq!(
+            ComposeItem::to_args(&compose_item),
+            vec![
+                "--env-file",
+                "test.env",
+                "--env-file",
+                ".env",
+                "--env-file",
+                "tests/.env.local",
+            ]
+        );
     }
 
     #[test]
@@ -318,8 +1094,9 @@ mod tests {
             use_project_name: None,
             description: Some(String::from("description")),
             compose_files: vec![String::from("docker-compose.yml")],
-            enviroment_file: None,
+            environment_file: None,
             status: None,
+            ..Default::default()
         };
 
         let args = ComposeItem::to_args(&compose_item);
@@ -331,12 +1108,120 @@ mod tests {
         assert!(args[3] == OsStr::new("docker-compose.yml"));
     }
 
+    fn value(value: &str) -> EnvValue {
+        EnvValue::Value(value.to_string())
+    }
+
+    #[test]
+    fn it_returns_the_environment_of_a_compose_item() {
+        let compose_item = ComposeItem {
+            alias: String::from("ci"),
+            context: Some(String::from("build-vm")),
+            docker_host: Some(String::from("ssh://ci@build-vm")),
+            env: Some(BTreeMap::from([
+                (String::from("DOCKER_HOST"), value("tcp://localhost:2375")),
+                (String::from("COMPOSE_PROFILES"), value("ci")),
+            ])),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            compose_item.to_env(Engine::Docker),
+            vec![
+                (String::from("COMPOSE_PROFILES"), value("ci")),
+                (String::from("DOCKER_CONTEXT"), value("build-vm")),
+                (String::from("DOCKER_HOST"), value("ssh://ci@build-vm")),
+            ]
+        );
+        assert!(ComposeItem::default().to_env(Engine::Docker).is_empty());
+
+        // Podman has connections instead of contexts
+        assert_eq!(
+            compose_item.to_env(Engine::Podman)[1],
+            (String::from("CONTAINER_CONNECTION"), value("build-vm"))
+        );
+    }
+
+    #[test]
+    fn it_reads_the_secret_values_of_the_environment() {
+        let content = r#"
+alias = "shop"
+compose_files = ["docker-compose.yml"]
+
+[env]
+DB_USER = "shop"
+DB_PASSWORD = { command = "pass show shop/db" }
+"#;
+        let compose_item: ComposeItem = toml::from_str(content).unwrap();
+
+        assert_eq!(
+            compose_item.to_env(Engine::Docker),
+            vec![
+                (
+                    String::from("DB_PASSWORD"),
+                    EnvValue::Secret {
+                        command: String::from("pass show shop/db")
+                    }
+                ),
+                (String::from("DB_USER"), value("shop")),
+            ]
+        );
+    }
+
+    fn get_command_args(
+        command_name: &str,
+        args: &[&str],
+        extend: Option<bool>,
+    ) -> DefaultCommandArgs {
+        DefaultCommandArgs {
+            command_name: command_name.to_string(),
+            command_args: args.iter().map(|arg| arg.to_string()).collect(),
+            extend,
+        }
+    }
+
+    #[test]
+    fn it_returns_the_project_default_command_args() {
+        let compose_item = ComposeItem {
+            alias: String::from("test"),
+            compose_files: vec![String::from("docker-compose.yml")],
+            default_command_args: Some(vec![
+                get_command_args("up", &["--build"], None),
+                get_command_args("down", &["-v"], Some(true)),
+            ]),
+            ..Default::default()
+        };
+
+        // Project args override the main ones
+        let args = compose_item
+            .get_default_command_args("up", Some(get_command_args("up", &["-d"], None)));
+        assert_eq!(args.unwrap().command_args, vec!["--build"]);
+
+        // Project args extend the main ones
+        let args = compose_item.get_default_command_args(
+            "down",
+            Some(get_command_args("down", &["--remove-orphans"], None)),
+        );
+        assert_eq!(args.unwrap().command_args, vec!["--remove-orphans", "-v"]);
+
+        let args = compose_item.get_default_command_args("down", None);
+        assert_eq!(args.unwrap().command_args, vec!["-v"]);
+
+        // Main args are kept when the project doesn't define any
+        let args = compose_item
+            .get_default_command_args("logs", Some(get_command_args("logs", &["-f"], None)));
+        assert_eq!(args.unwrap().command_args, vec!["-f"]);
+
+        let args = compose_item.get_default_command_args("logs", None);
+        assert!(args.is_none());
+    }
+
     #[test]
     fn it_build_a_undefined_default_command_args() {
         let command_args = DefaultCommandArgs::default("down");
 
         assert!(command_args.command_name == "down");
-        assert!(command_args.command_args.len() == 0);
+        assert!(command_args.command_args.is_empty());
     }
 
     #[test]
@@ -344,7 +1229,7 @@ mod tests {
         let command_args = DefaultCommandArgs::default("down");
         let args = DefaultCommandArgs::to_args(&command_args);
 
-        assert!(args.len() == 0);
+        assert!(args.is_empty());
     }
 
     #[test]
@@ -352,6 +1237,7 @@ mod tests {
         let command_args = DefaultCommandArgs {
             command_name: String::from("down"),
             command_args: vec![String::from("--volumes"), String::from("--remove-orphans")],
+            extend: None,
         };
 
         let args = DefaultCommandArgs::to_args(&command_args);
@@ -0,0 +1,175 @@
+//! Migrations of the config files to the current format version, keeping their comments
+
+use toml_edit::{value, DocumentMut, InlineTable, Item, Key, Table, Value};
+
+/// Version of the config format of this dctl, a file without `version` has the version 1
+pub const CONFIG_VERSION: i64 = 2;
+
+/// A change of the config format, returning whether the file was changed
+struct Migration {
+    version: i64,
+    description: &'static str,
+    migrate: fn(&mut DocumentMut) -> bool,
+}
+
+const MIGRATIONS: &[Migration] = &[Migration {
+    version: 2,
+    description: "rename enviroment_file to environment_file",
+    migrate: rename_environment_file,
+}];
+
+pub fn get_version(doc: &DocumentMut) -> i64 {
+    doc.get("version").and_then(Item::as_integer).unwrap_or(1)
+}
+
+/// Upgrade the file to the current version, and return the description of the
+/// migrations that changed it
+pub fn migrate(doc: &mut DocumentMut) -> Vec<&'static str> {
+    let version = get_version(doc);
+    if version >= CONFIG_VERSION {
+        return Vec::new();
+    }
+
+    let mut changes = Vec::new();
+    for migration in MIGRATIONS.iter().filter(|migration| migration.version > version) {
+        if (migration.migrate)(doc) {
+            changes.push(migration.description);
+        }
+    }
+    doc["version"] = value(CONFIG_VERSION);
+
+    changes
+}
+
+/// Deprecated content of a config file, that `dctl config migrate` would change
+pub fn get_deprecations(content: &str) -> Vec<&'static str> {
+    match content.parse::<DocumentMut>() {
+        Ok(mut doc) => migrate(&mut doc),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Projects of a config file, or the project of a project config file
+fn get_projects(doc: &mut DocumentMut) -> Vec<ProjectTable<'_>> {
+    if doc.contains_key("alias") {
+        return vec![ProjectTable::Table(doc.as_table_mut())];
+    }
+
+    match doc.get_mut("collections") {
+        Some(Item::ArrayOfTables(tables)) => tables.iter_mut().map(ProjectTable::Table).collect(),
+        Some(Item::Value(Value::Array(values))) => values
+            .iter_mut()
+            .filter_map(Value::as_inline_table_mut)
+            .map(ProjectTable::Inline)
+            .collect(),
+        _ => Vec::new(),
+    }
+}
+
+enum ProjectTable<'a> {
+    Table(&'a mut Table),
+    Inline(&'a mut InlineTable),
+}
+
+impl ProjectTable<'_> {
+    /// Rename a key, keeping its position and comments
+    fn rename_key(&mut self, from: &str, to: &str) -> bool {
+        match self {
+            ProjectTable::Table(table) => {
+                if !table.contains_key(from) || table.contains_key(to) {
+                    return false;
+                }
+                let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+                for key in keys {
+                    if let Some((key, item)) = table.remove_entry(&key) {
+                        table.insert_formatted(&renamed_key(key, from, to), item);
+                    }
+                }
+            }
+            ProjectTable::Inline(table) => {
+                if !table.contains_key(from) || table.contains_key(to) {
+                    return false;
+                }
+                let keys: Vec<String> = table.iter().map(|(key, _)| key.to_string()).collect();
+                for key in keys {
+                    if let Some((key, value)) = table.remove_entry(&key) {
+                        table.insert_formatted(&renamed_key(key, from, to), value);
+                    }
+                }
+            }
+        }
+
+        true
+    }
+}
+
+fn renamed_key(key: Key, from: &str, to: &str) -> Key {
+    if key.get() != from {
+        return key;
+    }
+
+    Key::new(to)
+        .with_leaf_decor(key.leaf_decor().clone())
+        .with_dotted_decor(key.dotted_decor().clone())
+}
+
+fn rename_environment_file(doc: &mut DocumentMut) -> bool {
+    let mut changed = false;
+    for mut project in get_projects(doc) {
+        changed |= project.rename_key("enviroment_file", "environment_file");
+    }
+
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_renames_the_environment_file_key() {
+        let content = r#"# My projects
+[main]
+docker_bin = "docker"
+
+[[collections]]
+alias = "web"
+# The env file of the project
+enviroment_file = "/home/test/web/.env" # Local one
+compose_files = ["/home/test/web/docker-compose.yml"]
+"#;
+        let mut doc = content.parse::<DocumentMut>().unwrap();
+        assert_eq!(migrate(&mut doc), vec!["rename enviroment_file to environment_file"]);
+
+        let expected = r#"version = 2
+# My projects
+[main]
+docker_bin = "docker"
+
+[[collections]]
+alias = "web"
+# The env file of the project
+environment_file = "/home/test/web/.env" # Local one
+compose_files = ["/home/test/web/docker-compose.yml"]
+"#;
+        assert_eq!(doc.to_string(), expected);
+        assert!(migrate(&mut doc).is_empty());
+    }
+
+    #[test]
+    fn it_migrates_a_project_config_file() {
+        let content = "alias = \"shop\"\nenviroment_file = \".env\"\n";
+        assert_eq!(get_deprecations(content).len(), 1);
+
+        let mut doc = content.parse::<DocumentMut>().unwrap();
+        migrate(&mut doc);
+        assert_eq!(doc.to_string(), "alias = \"shop\"\nenvironment_file = \".env\"\nversion = 2\n");
+    }
+
+    #[test]
+    fn it_only_sets_the_version_of_an_up_to_date_file() {
+        let content = "[main]\ndocker_bin = \"docker\"\n";
+        assert!(get_deprecations(content).is_empty());
+        assert!(get_deprecations("version = 2\n[[collections]]\nenviroment_file = \"\"\n").is_empty());
+    }
+}
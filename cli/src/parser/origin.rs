@@ -0,0 +1,171 @@
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use toml::de::{DeTable, DeValue};
+use toml::Spanned;
+
+/// File and line a config value comes from
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Origin {
+    pub file: PathBuf,
+    pub line: usize,
+}
+
+impl fmt::Display for Origin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.file.display(), self.line)
+    }
+}
+
+/// Origins of the config values: of the `[main]` values by key, a default command
+/// args being keyed by `default_command_args.<command_name>`, and of the fields of
+/// each project by alias
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConfigOrigins {
+    main: BTreeMap<String, Origin>,
+    projects: BTreeMap<String, BTreeMap<String, Origin>>,
+}
+
+impl ConfigOrigins {
+    pub fn get_main(&self, key: &str) -> Option<&Origin> {
+        self.main.get(key)
+    }
+
+    /// Origin of a field of a project, the origin of the project is the one of its alias
+    pub fn get_project(&self, alias: &str, field: &str) -> Option<&Origin> {
+        self.projects.get(alias).and_then(|fields| fields.get(field))
+    }
+
+    /// Origins of the values of a config file
+    pub fn from_config_file(content: &str, file: &Path) -> ConfigOrigins {
+        let mut origins = ConfigOrigins::default();
+        let Ok(root) = DeTable::parse(content) else {
+            return origins;
+        };
+        let origin = |span: usize| Origin {
+            file: file.to_path_buf(),
+            line: line_of(content, span),
+        };
+
+        if let Some(DeValue::Table(main)) = root.get_ref().get("main").map(Spanned::get_ref) {
+            for (key, value) in main.iter() {
+                match (key.get_ref().as_ref(), value.get_ref()) {
+                    ("default_command_args", DeValue::Array(args)) => {
+                        for arg in args.iter() {
+                            let DeValue::Table(table) = arg.get_ref() else {
+                                continue;
+                            };
+                            if let Some(command_name) = get_string(table, "command_name") {
+                                origins.main.insert(
+                                    format!("default_command_args.{}", command_name),
+                                    origin(arg.span().start),
+                                );
+                            }
+                        }
+                    }
+                    (name, _) => {
+                        origins.main.insert(name.to_string(), origin(key.span().start));
+                    }
+                }
+            }
+        }
+
+        if let Some(DeValue::Array(items)) = root.get_ref().get("collections").map(Spanned::get_ref)
+        {
+            for item in items.iter() {
+                if let DeValue::Table(table) = item.get_ref() {
+                    origins.insert_project(table, &origin);
+                }
+            }
+        }
+
+        origins
+    }
+
+    /// Origins of the values of a project config file, holding a single project
+    pub fn from_project_file(content: &str, file: &Path) -> ConfigOrigins {
+        let mut origins = ConfigOrigins::default();
+        if let Ok(root) = DeTable::parse(content) {
+            origins.insert_project(root.get_ref(), &|span| Origin {
+                file: file.to_path_buf(),
+                line: line_of(content, span),
+            });
+        }
+
+        origins
+    }
+
+    /// Override the origins with the ones of a higher layer, a project replaces
+    /// the origins of the project with the same alias
+    pub fn merge(&mut self, other: ConfigOrigins) {
+        self.main.extend(other.main);
+        self.projects.extend(other.projects);
+    }
+
+    fn insert_project(&mut self, table: &DeTable, origin: &dyn Fn(usize) -> Origin) {
+        let Some(alias) = get_string(table, "alias") else {
+            return;
+        };
+
+        let fields = table
+            .iter()
+            .map(|(key, _)| (key.get_ref().to_string(), origin(key.span().start)))
+            .collect();
+        self.projects.insert(alias, fields);
+    }
+}
+
+fn get_string(table: &DeTable, key: &str) -> Option<String> {
+    match table.get(key).map(Spanned::get_ref) {
+        Some(DeValue::String(value)) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+/// Line number of a byte offset
+fn line_of(content: &str, offset: usize) -> usize {
+    content[..offset.min(content.len())].matches('\n').count() + 1
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_config() -> &'static str {
+        r#"[main]
+docker_bin = "docker"
+default_command_args = [
+    { command_name = "up", command_args = ["-d"] },
+]
+
+[[collections]]
+alias = "web"
+compose_files = ["/home/test/web/docker-compose.yml"]
+"#
+    }
+
+    #[test]
+    fn it_returns_the_origin_of_each_value() {
+        let origins = ConfigOrigins::from_config_file(get_config(), Path::new("config.toml"));
+
+        let main = |key: &str| origins.get_main(key).unwrap().to_string();
+        assert_eq!(main("docker_bin"), "config.toml:2");
+        assert_eq!(main("default_command_args.up"), "config.toml:4");
+        let project = |field: &str| origins.get_project("web", field).unwrap().to_string();
+        assert_eq!(project("alias"), "config.toml:8");
+        assert_eq!(project("compose_files"), "config.toml:9");
+    }
+
+    #[test]
+    fn it_replaces_the_origins_of_a_project() {
+        let mut origins = ConfigOrigins::from_config_file(get_config(), Path::new("config.toml"));
+        let project = "alias = \"web\"\ndescription = \"Web\"\n";
+        origins.merge(ConfigOrigins::from_project_file(project, Path::new(".dctl.toml")));
+
+        assert_eq!(origins.get_project("web", "alias").unwrap().to_string(), ".dctl.toml:1");
+        assert_eq!(origins.get_project("web", "description").unwrap().line, 2);
+        assert!(origins.get_project("web", "compose_files").is_none());
+        assert_eq!(origins.get_main("docker_bin").unwrap().line, 2);
+    }
+}
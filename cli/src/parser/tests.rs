@@ -1,132 +1,411 @@
-#[cfg(test)]
-mod tests {
-    use crate::parser::config::*;
-
-    fn get_valid_config() -> String {
-        let config = r#"
-        [main]
-        docker_bin = "docker"
-        default_command_args = [
-            { command_name = "up", command_args = ["-d", "--remove-orphan"] },
-            { command_name = "down", command_args = ["-v"] },
-        ]
-        [[collections]]
-        alias = "test1"
-        description = "description 1"
-        enviroment_file = "/home/test/test1/.env"
-        compose_files = ["/home/test/test1/docker-compose.yml"]
-        
-
-        [[collections]]
-        alias = "test2"
-        enviroment_file = "/home/test/test2/.env"
-        compose_files = [
-            "/home/test/test2/docker-compose1.yml",
-            "/home/test/test2/docker-compose2.yml",
-        ]
+use std::path::{Path, PathBuf};
+
+use crate::parser::config::*;
+
+fn get_valid_config() -> String {
+    let config = r#"
+    [main]
+    docker_bin = "docker"
+    default_command_args = [
+        { command_name = "up", command_args = ["-d", "--remove-orphan"] },
+        { command_name = "down", command_args = ["-v"] },
+    ]
+    [[collections]]
+    alias = "test1"
+    description = "description 1"
+    environment_file = "/home/test/test1/.env"
+    compose_files = ["/home/test/test1/docker-compose.yml"]
+    
+
+    [[collections]]
+    alias = "test2"
+    environment_file = "/home/test/test2/.env"
+    compose_files = [
+        "/home/test/test2/docker-compose1.yml",
+        "/home/test/test2/docker-compose2.yml",
+    ]
+
+    [[collections]]
+    alias = "test3"
+    description = "description 3"
+    groups = ["back"]
+    compose_files = [
+        "/home/test/test3/docker-compose.yml"
+    ]
+
+    [[collections]]
+    alias = "test4"
+    tags = ["back", "tools"]
+    compose_files = [
+        "/home/test/test4/docker-compose.yml"
+    ]
+    default_command_args = [
+        { command_name = "up", command_args = ["--build"] },
+        { command_name = "down", command_args = ["--remove-orphans"], extend = true },
+    ]
+    "#;
+
+    config.to_string()
+}
+
+#[test]
+fn it_loads_a_valid_config() {
+    let config = DctlConfig::load_layers(&[PathBuf::from("tests/valid_config.toml")]);
+    assert!(config.is_ok());
+}
+
+#[test]
+fn it_loads_a_unvalid_config() {
+    let config = DctlConfig::load_layers(&[PathBuf::from("tests/bad_config.toml")]);
+    assert!(config.is_err());
+}
+
+#[test]
+fn it_returns_a_valid_alias_item() {
+    let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+    let item = config.get_compose_item_by_alias(String::from("test1"));
+    assert!(item.is_some());
+}
+
+#[test]
+fn it_returns_an_unvalid_alias_item() {
+    let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+    let item = config.get_compose_item_by_alias(String::from("test"));
+    assert!(item.is_none());
+}
+
+#[test]
+fn it_returns_all_compose_items() {
+    let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+    let items = config.get_all_compose_items();
+    assert!(4 == items.len());
+}
+
+#[test]
+fn it_returns_item_attributes_values_for_test1() {
+    let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+    let item = config
+        .get_compose_item_by_alias(String::from("test1"))
+        .unwrap();
+    assert!(item.alias == "test1");
+    assert!(item.description.unwrap() == "description 1");
+    assert!(item.environment_file.unwrap() == "/home/test/test1/.env");
+    assert!(item.compose_files.len() == 1);
+    assert!(item.compose_files[0] == "/home/test/test1/docker-compose.yml");
+}
+
+#[test]
+fn it_returns_item_attributes_values_for_test2() {
+    let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+    let item = config
+        .get_compose_item_by_alias(String::from("test2"))
+        .unwrap();
+    assert!(item.alias == "test2");
+    assert!(item.description.is_none());
+    assert!(item.environment_file.unwrap() == "/home/test/test2/.env");
+    assert!(item.compose_files.len() == 2);
+    assert!(item.compose_files[0] == "/home/test/test2/docker-compose1.yml");
+    assert!(item.compose_files[1] == "/home/test/test2/docker-compose2.yml");
+}
+
+#[test]
+fn it_returns_item_attributes_values_for_test3() {
+    let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+    let item = config
+        .get_compose_item_by_alias(String::from("test3"))
+        .unwrap();
+    assert!(item.alias == "test3");
+    assert!(item.description.unwrap() == "description 3");
+    assert!(item.environment_file.is_none());
+    assert!(item.compose_files.len() == 1);
+    assert!(item.compose_files[0] == "/home/test/test3/docker-compose.yml");
+}
+
+#[test]
+fn it_returns_declared_defautlt_command_args() {
+    let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+
+    let args = config.get_default_command_args("up");
+    assert!(args.is_some());
+    let args = args.unwrap();
+    assert!(args.command_args.len() == 2);
+    assert!(args.command_args[0] == "-d");
+    assert!(args.command_args[1] == "--remove-orphan");
+
+    let args = config.get_default_command_args("down");
+    assert!(args.is_some());
+    let args = args.unwrap();
+    assert!(args.command_args.len() == 1);
+    assert!(args.command_args[0] == "-v");
+
+    let args = config.get_default_command_args("other");
+    assert!(args.is_none());
+}
+
+#[test]
+fn it_returns_compose_items_by_group() {
+    let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+
+    let items = config.get_compose_items_by_group(String::from("back"));
+    assert!(items.len() == 2);
+    assert!(items[0].alias == "test3");
+    assert!(items[1].alias == "test4");
+
+    let items = config.get_compose_items_by_group(String::from("tools"));
+    assert!(items.len() == 1);
+    assert!(items[0].alias == "test4");
+
+    let items = config.get_compose_items_by_group(String::from("other"));
+    assert!(items.is_empty());
+}
+
+#[test]
+fn it_returns_project_default_command_args() {
+    let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+    let item = config
+        .get_compose_item_by_alias(String::from("test4"))
+        .unwrap();
+
+    let args = item.get_default_command_args("up", config.get_default_command_args("up"));
+    assert!(args.unwrap().command_args == vec!["--build"]);
+
+    let args = item.get_default_command_args("down", config.get_default_command_args("down"));
+    assert!(args.unwrap().command_args == vec!["-v", "--remove-orphans"]);
+}
+
+fn get_aliases_config() -> DctlConfig {
+    let config = r#"
+    [main]
+    docker_bin = "docker"
+
+    [[collections]]
+    alias = "web"
+    compose_files = ["/home/test/web/docker-compose.yml"]
+
+    [[collections]]
+    alias = "stack_monitoring"
+    compose_files = ["/home/test/monitoring/docker-compose.yml"]
 
-        [[collections]]
-        alias = "test3"
-        description = "description 3"
-        compose_files = [
-            "/home/test/test3/docker-compose.yml"
+    [[collections]]
+    alias = "stack_logging"
+    compose_files = ["/home/test/logging/docker-compose.yml"]
+    "#;
+
+    toml::from_str(config).unwrap()
+}
+
+#[test]
+fn it_resolves_an_alias_from_a_unique_prefix() {
+    let config = get_aliases_config();
+    assert_eq!(config.resolve_compose_item("web").unwrap().alias, "web");
+    assert_eq!(config.resolve_compose_item("we").unwrap().alias, "web");
+    assert_eq!(config.resolve_compose_item("stack_m").unwrap().alias, "stack_monitoring");
+}
+
+#[test]
+fn it_lists_the_candidates_of_an_ambiguous_prefix() {
+    let config = get_aliases_config();
+    let error = config.resolve_compose_item("stack").unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Alias stack matches several projects: stack_logging, stack_monitoring"
+    );
+}
+
+#[test]
+fn it_suggests_the_closest_aliases_on_a_miss() {
+    let config = get_aliases_config();
+    let error = config.resolve_compose_item("wbe").unwrap_err();
+    assert_eq!(error.to_string(), "No project found with alias: wbe, did you mean: web?");
+}
+
+#[test]
+fn it_returns_the_compose_item_of_a_directory() {
+    let config = r#"
+    [main]
+    docker_bin = "docker"
+
+    [[collections]]
+    alias = "shop"
+    compose_files = ["/home/test/work/shop/docker-compose.yml"]
+
+    [[collections]]
+    alias = "shop_admin"
+    compose_files = ["/home/test/work/shop/admin/docker-compose.yml"]
+
+    [[collections]]
+    alias = "relative"
+    compose_files = ["docker-compose.yml"]
+    "#;
+    let config: DctlConfig = toml::from_str(config).unwrap();
+
+    let item = config.get_compose_item_by_dir(Path::new("/home/test/work/shop/src/php"));
+    assert_eq!(item.unwrap().alias, "shop");
+    // The closest project wins when projects are nested
+    let item = config.get_compose_item_by_dir(Path::new("/home/test/work/shop/admin"));
+    assert_eq!(item.unwrap().alias, "shop_admin");
+    assert!(config.get_compose_item_by_dir(Path::new("/home/test/work")).is_none());
+}
+
+#[test]
+fn it_finds_the_project_config_file_from_a_sub_directory() {
+    let path = DctlConfig::find_project_config_file(Path::new("tests/project/src/php"));
+    assert_eq!(path, Some(Path::new("tests/project").join(PROJECT_CONFIG_FILE)));
+    assert!(DctlConfig::find_project_config_file(Path::new("/")).is_none());
+}
+
+#[test]
+fn it_loads_a_project_config_file_with_relative_paths() {
+    let path = Path::new("/home/test/tests/project").join(PROJECT_CONFIG_FILE);
+    let content = std::fs::read_to_string(Path::new("tests/project").join(PROJECT_CONFIG_FILE));
+    let mut item: ComposeItem = toml::from_str(&content.unwrap()).unwrap();
+    item.resolve_paths(path.parent().unwrap());
+
+    assert_eq!(item.alias, "shop");
+    assert_eq!(item.environment_file.unwrap(), "/home/test/tests/project/.env");
+    assert_eq!(
+        item.compose_files,
+        vec![
+            "/home/test/tests/project/docker-compose.yml",
+            "/home/test/tests/docker-compose.test.yml",
         ]
-        "#;
-
-        config.to_string()
-    }
-
-    #[test]
-    fn it_loads_a_valid_config() {
-        let config = DctlConfig::load("tests/valid_config.toml".to_string());
-        assert!(config.is_ok());
-    }
-
-    #[test]
-    fn it_loads_a_unvalid_config() {
-        let config = DctlConfig::load("tests/bad_config.toml".to_string());
-        assert!(config.is_err());
-    }
-
-    #[test]
-    fn it_returns_a_valid_alias_item() {
-        let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
-        let item = config.get_compose_item_by_alias(String::from("test1"));
-        assert!(item.is_some());
-    }
-
-    #[test]
-    fn it_returns_an_unvalid_alias_item() {
-        let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
-        let item = config.get_compose_item_by_alias(String::from("test"));
-        assert!(item.is_none());
-    }
-
-    #[test]
-    fn it_returns_all_compose_items() {
-        let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
-        let items = config.get_all_compose_items();
-        assert!(3 == items.len());
-    }
-
-    #[test]
-    fn it_returns_item_attributes_values_for_test1() {
-        let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
-        let item = config
-            .get_compose_item_by_alias(String::from("test1"))
-            .unwrap();
-        assert!(item.alias == "test1");
-        assert!(item.description.unwrap() == "description 1");
-        assert!(item.enviroment_file.unwrap() == "/home/test/test1/.env");
-        assert!(item.compose_files.len() == 1);
-        assert!(item.compose_files[0] == "/home/test/test1/docker-compose.yml");
-    }
-
-    #[test]
-    fn it_returns_item_attributes_values_for_test2() {
-        let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
-        let item = config
-            .get_compose_item_by_alias(String::from("test2"))
-            .unwrap();
-        assert!(item.alias == "test2");
-        assert!(item.description.is_none());
-        assert!(item.enviroment_file.unwrap() == "/home/test/test2/.env");
-        assert!(item.compose_files.len() == 2);
-        assert!(item.compose_files[0] == "/home/test/test2/docker-compose1.yml");
-        assert!(item.compose_files[1] == "/home/test/test2/docker-compose2.yml");
-    }
-
-    #[test]
-    fn it_returns_item_attributes_values_for_test3() {
-        let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
-        let item = config
-            .get_compose_item_by_alias(String::from("test3"))
-            .unwrap();
-        assert!(item.alias == "test3");
-        assert!(item.description.unwrap() == "description 3");
-        assert!(item.enviroment_file.is_none());
-        assert!(item.compose_files.len() == 1);
-        assert!(item.compose_files[0] == "/home/test/test3/docker-compose.yml");
-    }
-
-    #[test]
-    fn it_returns_declared_defautlt_command_args() {
-        let config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
-
-        let args = config.get_default_command_args("up");
-        assert!(args.is_some());
-        let args = args.unwrap();
-        assert!(args.command_args.len() == 2);
-        assert!(args.command_args[0] == "-d");
-        assert!(args.command_args[1] == "--remove-orphan");
-
-        let args = config.get_default_command_args("down");
-        assert!(args.is_some());
-        let args = args.unwrap();
-        assert!(args.command_args.len() == 1);
-        assert!(args.command_args[0] == "-v");
-
-        let args = config.get_default_command_args("other");
-        assert!(args.is_none());
-    }
+    );
+}
+
+#[test]
+fn it_merges_a_project_config_file() {
+    let mut config: DctlConfig = toml::from_str(get_valid_config().as_str()).unwrap();
+    let (item, _) = DctlConfig::load_project_config_file(
+        &Path::new("tests/project").join(PROJECT_CONFIG_FILE),
+    )
+    .unwrap();
+    config.merge_compose_item(item);
+    assert_eq!(config.get_all_compose_items().len(), 5);
+
+    // A project with the same alias is replaced
+    let item = ComposeItem {
+        alias: String::from("test1"),
+        compose_files: vec![String::from("/home/test/other/docker-compose.yml")],
+        ..Default::default()
+    };
+    config.merge_compose_item(item);
+    let items = config.get_all_compose_items();
+    assert_eq!(items.len(), 5);
+    assert_eq!(items[0].compose_files, vec!["/home/test/other/docker-compose.yml"]);
+}
+
+#[test]
+fn it_loads_the_included_config_files_in_order() {
+    let config = DctlConfig::load_layers(&[PathBuf::from("tests/include/config.toml")]).unwrap();
+    let aliases: Vec<String> = config
+        .get_all_compose_items()
+        .into_iter()
+        .map(|item| item.alias)
+        .collect();
+    assert_eq!(aliases, vec!["main_project", "team_project", "blog", "shop"]);
+
+    // Relative paths are resolved from the directory of the file declaring them
+    let dir = std::env::current_dir().unwrap().join("tests/include");
+    let compose_files = |alias: &str| {
+        let item = config.get_compose_item_by_alias(alias.to_string()).unwrap();
+        PathBuf::from(&item.compose_files[0])
+    };
+    assert_eq!(compose_files("main_project"), dir.join("main/docker-compose.yml"));
+    assert_eq!(compose_files("blog"), dir.join("blog/docker-compose.yml"));
+    assert_eq!(compose_files("shop"), PathBuf::from("/home/test/shop/docker-compose.yml"));
+}
+
+#[test]
+fn it_reports_an_alias_declared_in_two_config_files() {
+    let files = [PathBuf::from("tests/include_duplicate.toml")];
+    let error = DctlConfig::load_layers(&files).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "alias shop is declared in tests/include_duplicate.toml and tests/include/projects/b_shop.toml"
+    );
+}
+
+#[test]
+fn it_overrides_the_lower_config_layers() {
+    let files: Vec<PathBuf> = ["system", "team", "user"]
+        .iter()
+        .map(|layer| PathBuf::from(format!("tests/layers/{}.toml", layer)))
+        .collect();
+    let config = DctlConfig::load_layers(&files).unwrap();
+
+    assert_eq!(config.get_container_bin_path().unwrap(), "/usr/local/bin/docker");
+    assert_eq!(config.get_default_command_args("up").unwrap().command_args, vec!["-d", "--build"]);
+    assert_eq!(config.get_default_command_args("down").unwrap().command_args, vec!["-v"]);
+
+    let web = config.get_compose_item_by_alias(String::from("web")).unwrap();
+    assert_eq!(web.description, None);
+    assert_eq!(web.compose_files, vec!["/home/test/web/docker-compose.yml"]);
+    assert!(config.get_compose_item_by_alias(String::from("shop")).is_some());
+
+    let origin = |alias: Option<&str>, key: &str| config.get_origin(alias, key).unwrap().to_string();
+    assert_eq!(origin(None, "docker_bin"), "tests/layers/user.toml:2");
+    assert_eq!(origin(None, "default_command_args.up"), "tests/layers/team.toml:3");
+    assert_eq!(origin(None, "default_command_args.down"), "tests/layers/system.toml:5");
+    assert_eq!(origin(Some("web"), "alias"), "tests/layers/user.toml:5");
+    assert!(config.get_origin(Some("web"), "description").is_none());
+}
+
+#[test]
+fn it_needs_a_docker_bin_in_a_config_layer() {
+    let files = vec![PathBuf::from("tests/layers/team.toml")];
+    let error = DctlConfig::load_layers(&files).unwrap_err();
+    assert_eq!(error.to_string(), "docker_bin is not set in the [main] section");
+}
+
+#[test]
+fn it_expands_the_environment_variables_of_the_config() {
+    std::env::set_var("DCTL_TEST_WORKSPACE", "/home/test/work");
+    let files = vec![PathBuf::from("tests/interpolation/config.toml")];
+    let config = DctlConfig::load_layers(&files).unwrap();
+
+    assert_eq!(config.get_container_bin_path().unwrap(), "docker");
+    assert_eq!(
+        config.get_default_command_args("up").unwrap().command_args,
+        vec!["--env-file", "/home/test/work/.env"]
+    );
+    let shop = config.get_compose_item_by_alias(String::from("shop")).unwrap();
+    assert_eq!(shop.compose_files, vec!["/home/test/work/shop/compose.yaml"]);
+    assert!(!shop.environment_file.unwrap().starts_with('~'));
+    assert_eq!(
+        shop.env.unwrap()["DB_PASSWORD"],
+        EnvValue::Value(String::from("pa$sw0rd${DCTL_TEST_WORKSPACE}"))
+    );
+}
+
+#[test]
+fn it_reports_an_unset_environment_variable_of_the_config() {
+    let files = vec![PathBuf::from("tests/interpolation/unset.toml")];
+    let error = DctlConfig::load_layers(&files).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "environment variable DCTL_TEST_UNSET_WORKSPACE is not set, used in tests/interpolation/unset.toml"
+    );
+}
+
+#[test]
+fn it_refuses_a_config_file_of_a_newer_version() {
+    let files = vec![PathBuf::from("tests/future_version.toml")];
+    let error = DctlConfig::load_layers(&files).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "tests/future_version.toml has the config version 3, this dctl supports up to 2"
+    );
+}
+
+#[test]
+fn it_loads_the_deprecated_environment_file_key() {
+    let item: ComposeItem = toml::from_str(
+        r#"
+        alias = "web"
+        enviroment_file = "/home/test/web/.env"
+        compose_files = ["/home/test/web/docker-compose.yml"]
+        "#,
+    )
+    .unwrap();
+    assert_eq!(item.environment_file.unwrap(), "/home/test/web/.env");
 }
@@ -0,0 +1,335 @@
+//! Validation of the config files, each problem being reported with its position
+
+use std::ops::Range;
+use std::path::Path;
+
+use toml::de::{DeTable, DeValue};
+use toml::Spanned;
+
+use crate::utils::error::DctlError;
+use crate::utils::suggest::closest_names;
+
+/// Keys of a config file of a layer
+const CONFIG_KEYS: &[&str] = &["version", "main", "collections"];
+/// Keys of an included config file
+const INCLUDED_KEYS: &[&str] = &["version", "collections"];
+const MAIN_KEYS: &[&str] = &["docker_bin", "engine", "default_command_args", "include"];
+const PROJECT_KEYS: &[&str] = &[
+    "alias",
+    "description",
+    "use_project_name",
+    "environment_file",
+    "enviroment_file",
+    "env_files",
+    "compose_files",
+    "groups",
+    "tags",
+    "profiles",
+    "default_command_args",
+    "engine",
+    "docker_bin",
+    "context",
+    "docker_host",
+    "env",
+];
+const COMMAND_ARGS_KEYS: &[&str] = &["command_name", "command_args", "extend"];
+const ENV_FILE_KEYS: &[&str] = &["path", "required"];
+
+/// Kind of config file, setting the keys it holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFileKind {
+    /// The system, team or user config file
+    Layer,
+    /// A file of the conf.d directory, or matching an include pattern
+    Included,
+    /// The `.dctl.toml` file of a project
+    Project,
+}
+
+/// Message pointing at a span of a config file, as `file:line:column: message`
+/// followed by the line and a caret under the span
+pub fn format_diagnostic(content: &str, path: &Path, span: Range<usize>, message: &str) -> String {
+    let start = span.start.min(content.len());
+    let line_start = content[..start].rfind('\n').map_or(0, |index| index + 1);
+    let line_end = content[start..].find('\n').map_or(content.len(), |index| start + index);
+    let line = &content[line_start..line_end];
+    let line_number = content[..start].matches('\n').count() + 1;
+    let column = content[line_start..start].chars().count() + 1;
+    let width = content[start..span.end.clamp(start, line_end)].chars().count().max(1);
+
+    let margin = " ".repeat(line_number.to_string().len());
+    format!(
+        "{}:{}:{}: {}\n{} |\n{} | {}\n{} | {}{}",
+        path.display(),
+        line_number,
+        column,
+        message,
+        margin,
+        line_number,
+        line,
+        margin,
+        " ".repeat(column - 1),
+        "^".repeat(width)
+    )
+}
+
+/// Error of a config file which can't be parsed, or doesn't match the config structure
+pub fn parse_error(content: &str, path: &Path, error: &toml::de::Error) -> DctlError {
+    let message = error.message().trim_end();
+    DctlError::Config(match error.span() {
+        Some(span) => format_diagnostic(content, path, span, message),
+        None => format!("{}: {}", path.display(), message),
+    })
+}
+
+/// Check a config file before loading it: an unknown key is a warning, with the closest
+/// known key, and an alias declared twice or a project without compose files is an error
+pub fn validate_config_file(
+    content: &str,
+    path: &Path,
+    kind: ConfigFileKind,
+) -> Result<Vec<String>, DctlError> {
+    let root = DeTable::parse(content).map_err(|err| parse_error(content, path, &err))?;
+    let mut validator = Validator {
+        content,
+        path,
+        warnings: Vec::new(),
+    };
+
+    match kind {
+        ConfigFileKind::Project => validator.check_project(root.get_ref(), true)?,
+        ConfigFileKind::Layer | ConfigFileKind::Included => {
+            let keys = if kind == ConfigFileKind::Layer { CONFIG_KEYS } else { INCLUDED_KEYS };
+            // An unknown section would drop all its values, it's an error
+            for (key, _) in root.get_ref().iter() {
+                if !keys.contains(&key.get_ref().as_ref()) {
+                    return Err(validator.error(key.span(), &unknown_key(key, "the config file", keys)));
+                }
+            }
+            if let Some(DeValue::Table(main)) = get_value(root.get_ref(), "main") {
+                validator.check_keys(main, "[main]", MAIN_KEYS);
+                validator.check_command_args(main);
+            }
+            if let Some(DeValue::Array(items)) = get_value(root.get_ref(), "collections") {
+                validator.check_projects(items)?;
+            }
+        }
+    }
+
+    Ok(validator.warnings)
+}
+
+struct Validator<'a> {
+    content: &'a str,
+    path: &'a Path,
+    warnings: Vec<String>,
+}
+
+impl Validator<'_> {
+    fn error(&self, span: Range<usize>, message: &str) -> DctlError {
+        DctlError::Config(format_diagnostic(self.content, self.path, span, message))
+    }
+
+    fn check_keys(&mut self, table: &DeTable, context: &str, keys: &[&str]) {
+        for (key, _) in table.iter() {
+            if !keys.contains(&key.get_ref().as_ref()) {
+                let message = unknown_key(key, context, keys);
+                self.warnings.push(format_diagnostic(self.content, self.path, key.span(), &message));
+            }
+        }
+    }
+
+    fn check_command_args(&mut self, table: &DeTable) {
+        if let Some(DeValue::Array(args)) = get_value(table, "default_command_args") {
+            for arg in args.iter() {
+                if let DeValue::Table(arg) = arg.get_ref() {
+                    self.check_keys(arg, "default_command_args", COMMAND_ARGS_KEYS);
+                }
+            }
+        }
+    }
+
+    fn check_projects(&mut self, items: &[Spanned<DeValue>]) -> Result<(), DctlError> {
+        let mut aliases: Vec<(String, usize)> = Vec::new();
+        for item in items {
+            let DeValue::Table(table) = item.get_ref() else {
+                continue;
+            };
+            self.check_project(table, false)?;
+
+            let Some((alias, span)) = get_alias(table) else {
+                continue;
+            };
+            if let Some((_, line)) = aliases.iter().find(|(declared, _)| *declared == alias) {
+                return Err(self.error(
+                    span,
+                    &format!("alias {} is already declared at line {}", alias, line),
+                ));
+            }
+            let line = self.content[..span.start].matches('\n').count() + 1;
+            aliases.push((alias, line));
+        }
+
+        Ok(())
+    }
+
+    fn check_project(&mut self, table: &DeTable, project_file: bool) -> Result<(), DctlError> {
+        let context = match get_alias(table) {
+            Some((alias, _)) => format!("project {}", alias),
+            None => String::from("project"),
+        };
+        let mut keys = PROJECT_KEYS.to_vec();
+        if project_file {
+            keys.push("version");
+        }
+        self.check_keys(table, &context, &keys);
+        self.check_command_args(table);
+        if let Some(DeValue::Array(env_files)) = get_value(table, "env_files") {
+            for env_file in env_files.iter() {
+                if let DeValue::Table(env_file) = env_file.get_ref() {
+                    self.check_keys(env_file, "env_files", ENV_FILE_KEYS);
+                }
+            }
+        }
+
+        if let Some((key, value)) = table.get_key_value("compose_files") {
+            if matches!(value.get_ref(), DeValue::Array(files) if files.is_empty()) {
+                return Err(self.error(key.span(), &format!("{} has no compose files", context)));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn get_value<'a, 'i>(table: &'a DeTable<'i>, key: &str) -> Option<&'a DeValue<'i>> {
+    table.get(key).map(Spanned::get_ref)
+}
+
+fn get_alias(table: &DeTable) -> Option<(String, Range<usize>)> {
+    match table.get("alias") {
+        Some(value) => match value.get_ref() {
+            DeValue::String(alias) => Some((alias.to_string(), value.span())),
+            _ => None,
+        },
+        None => None,
+    }
+}
+
+fn unknown_key(key: &Spanned<std::borrow::Cow<str>>, context: &str, keys: &[&str]) -> String {
+    let mut message = format!("unknown key `{}` in {}", key.get_ref(), context);
+    if let Some(suggestion) = closest_names(key.get_ref(), keys.iter().copied()).first() {
+        message.push_str(&format!(", did you mean `{}`?", suggestion));
+    }
+
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeSet;
+
+    use schemars::schema_for;
+    use serde_json::Value;
+
+    use super::*;
+    use crate::parser::config::ConfigLayer;
+
+    fn validate(content: &str, kind: ConfigFileKind) -> Result<Vec<String>, DctlError> {
+        validate_config_file(content, Path::new("config.toml"), kind)
+    }
+
+    fn get_property_names(schema: &Value) -> BTreeSet<&str> {
+        schema["properties"].as_object().unwrap().keys().map(String::as_str).collect()
+    }
+
+    fn get_keys<'a>(keys: &[&'a str]) -> BTreeSet<&'a str> {
+        keys.iter().copied().collect()
+    }
+
+    #[test]
+    fn it_knows_the_keys_of_the_config_structure() {
+        let schema = schema_for!(ConfigLayer).to_value();
+        let definitions = &schema["$defs"];
+
+        assert_eq!(get_keys(CONFIG_KEYS), get_property_names(&schema));
+        let mut included_keys = get_property_names(&schema);
+        included_keys.remove("main");
+        assert_eq!(get_keys(INCLUDED_KEYS), included_keys);
+        assert_eq!(get_keys(MAIN_KEYS), get_property_names(&definitions["MainLayer"]));
+        // The schema lists the deprecated names of the project keys too
+        assert_eq!(get_keys(PROJECT_KEYS), get_property_names(&definitions["ComposeItem"]));
+        assert_eq!(
+            get_keys(COMMAND_ARGS_KEYS),
+            get_property_names(&definitions["DefaultCommandArgs"])
+        );
+        assert_eq!(
+            get_keys(ENV_FILE_KEYS),
+            get_property_names(&definitions["EnvFile"]["anyOf"][1])
+        );
+    }
+
+    #[test]
+    fn it_points_at_the_position_of_a_problem() {
+        let content = "[main]\ndocker_bin = 1\n";
+        let diagnostic = format_diagnostic(content, Path::new("config.toml"), 20..21, "invalid");
+        assert_eq!(diagnostic, "config.toml:2:14: invalid\n  |\n2 | docker_bin = 1\n  |              ^");
+    }
+
+    #[test]
+    fn it_reports_the_position_of_a_parse_error() {
+        let error = validate("[main]\ndocker_bin = \n", ConfigFileKind::Layer).unwrap_err();
+        assert!(error.to_string().starts_with("config.toml:2:14: "));
+        assert!(error.to_string().ends_with("2 | docker_bin = \n  |              ^"));
+    }
+
+    #[test]
+    fn it_warns_about_the_unknown_keys() {
+        let content = r#"[main]
+docker_bin = "docker"
+
+[[collections]]
+alias = "web"
+descripton = "Web"
+compose_file = ["/home/test/web/docker-compose.yml"]
+"#;
+        let warnings = validate(content, ConfigFileKind::Layer).unwrap();
+        assert_eq!(warnings.len(), 2);
+        assert!(warnings[0].starts_with(
+            "config.toml:7:1: unknown key `compose_file` in project web, did you mean `compose_files`?"
+        ));
+        assert!(warnings[1].starts_with(
+            "config.toml:6:1: unknown key `descripton` in project web, did you mean `description`?"
+        ));
+    }
+
+    #[test]
+    fn it_warns_about_the_unknown_keys_of_an_env_file() {
+        let content = "alias = \"web\"\nenv_files = [{ path = \".env\", optional = true }]\n";
+        let warnings = validate(content, ConfigFileKind::Project).unwrap();
+        assert_eq!(warnings.len(), 1);
+        assert!(warnings[0].starts_with("config.toml:2:31: unknown key `optional` in env_files"));
+    }
+
+    #[test]
+    fn it_refuses_an_unknown_section() {
+        let error = validate("[[collection]]\nalias = \"web\"\n", ConfigFileKind::Layer).unwrap_err();
+        assert!(error.to_string().starts_with(
+            "config.toml:1:3: unknown key `collection` in the config file, did you mean `collections`?"
+        ));
+    }
+
+    #[test]
+    fn it_refuses_an_alias_declared_twice() {
+        let content = "[[collections]]\nalias = \"web\"\ncompose_files = [\"a.yml\"]\n\n\
+            [[collections]]\nalias = \"web\"\ncompose_files = [\"b.yml\"]\n";
+        let error = validate(content, ConfigFileKind::Included).unwrap_err();
+        assert!(error.to_string().starts_with("config.toml:6:9: alias web is already declared at line 2"));
+    }
+
+    #[test]
+    fn it_refuses_a_project_without_compose_files() {
+        let error = validate("alias = \"web\"\ncompose_files = []\n", ConfigFileKind::Project).unwrap_err();
+        assert!(error.to_string().starts_with("config.toml:2:1: project web has no compose files"));
+    }
+}
@@ -1,4 +1,10 @@
+pub mod diff;
 pub mod docker;
+pub mod dry_run;
+pub mod engine;
+pub mod error;
+pub mod secret;
+pub mod suggest;
 pub mod system;
 
 #[cfg(test)]
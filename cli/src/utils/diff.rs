@@ -0,0 +1,126 @@
+//! Line diff of two texts, in the unified format
+
+/// Unchanged lines printed around each change
+const CONTEXT_LINES: usize = 2;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Changes turning the old lines into the new ones, from their longest common subsequence
+fn diff_lines<'a>(old: &[&'a str], new: &[&'a str]) -> Vec<Change<'a>> {
+    // common[i][j] is the length of the common subsequence of old[i..] and new[j..]
+    let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Change::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            changes.push(Change::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(new[j]));
+            j += 1;
+        }
+    }
+
+    changes
+}
+
+/// Unified diff of the two versions of a file, empty when they are the same
+pub fn unified_diff(old: &str, new: &str, path: &str) -> String {
+    let old_lines: Vec<&str> = old.lines().collect();
+    let new_lines: Vec<&str> = new.lines().collect();
+    let changes = diff_lines(&old_lines, &new_lines);
+
+    // Group the changes into hunks, with their context lines
+    let mut hunks: Vec<(usize, usize)> = Vec::new();
+    for (index, change) in changes.iter().enumerate() {
+        if matches!(change, Change::Same(_)) {
+            continue;
+        }
+        let start = index.saturating_sub(CONTEXT_LINES);
+        let end = (index + CONTEXT_LINES + 1).min(changes.len());
+        match hunks.last_mut() {
+            Some((_, last_end)) if start <= *last_end => *last_end = end,
+            _ => hunks.push((start, end)),
+        }
+    }
+    if hunks.is_empty() {
+        return String::new();
+    }
+
+    let mut output = format!("--- {}\n+++ {}\n", path, path);
+    for (start, end) in hunks {
+        // Line numbers of the hunk start, in the old and new files
+        let old_start = changes[..start].iter().filter(|c| !matches!(c, Change::Added(_))).count();
+        let new_start = changes[..start].iter().filter(|c| !matches!(c, Change::Removed(_))).count();
+        let hunk = &changes[start..end];
+        let old_count = hunk.iter().filter(|c| !matches!(c, Change::Added(_))).count();
+        let new_count = hunk.iter().filter(|c| !matches!(c, Change::Removed(_))).count();
+
+        output.push_str(&format!(
+            "@@ -{},{} +{},{} @@\n",
+            old_start + 1,
+            old_count,
+            new_start + 1,
+            new_count
+        ));
+        for change in hunk {
+            let line = match change {
+                Change::Same(line) => format!(" {}", line),
+                Change::Removed(line) => format!("-{}", line),
+                Change::Added(line) => format!("+{}", line),
+            };
+            output.push_str(&line);
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_returns_no_diff_for_the_same_text() {
+        assert_eq!(unified_diff("a\nb\n", "a\nb\n", "config.toml"), "");
+    }
+
+    #[test]
+    fn it_returns_the_changed_lines_with_their_context() {
+        let old = "a\nb\nc\nd\ne\nf\ng\n";
+        let new = "a\nb\nc\nD\ne\nf\ng\nh\n";
+        let expected = "--- config.toml
++++ config.toml
+@@ -2,6 +2,7 @@
+ b
+ c
+-d
++D
+ e
+ f
+ g
++h
+";
+        assert_eq!(unified_diff(old, new, "config.toml"), expected);
+    }
+}
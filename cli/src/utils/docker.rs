@@ -4,7 +4,9 @@ use anyhow::{anyhow, Result};
 use std::ffi::{OsStr, OsString};
 use std::process::Output;
 
+use crate::command::args::ProjectTarget;
 use crate::command::registry::get_command_by_name;
+use super::engine::Engine;
 use super::system::System;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -92,43 +94,59 @@ impl CommandType {
 pub enum CommandOutput {
     Status,
     Output,
+    /// Stream stdout/stderr line by line, each line prefixed with the given tag
+    Stream(String),
 }
 
+/// Container manager running the compose commands with the binary of an engine
 #[derive(Debug, PartialEq)]
-pub struct Docker {
+pub struct ComposeEngine {
+    pub engine: Engine,
     pub bin_path: String,
 }
 
 #[async_trait]
 pub trait Container {
-    fn init(bin_path: String) -> Self
-    where
-        Self: Sized;
+    /// Engine of the container, running the compose commands
+    fn engine(&self) -> Engine;
+    /// Run a compose command, `config_env` being added to the environment of the child,
+    /// `target` telling which positional values are projects
+    #[allow(clippy::too_many_arguments)]
     async fn compose(
         &self,
         command_type: CommandType,
-        config_args: &Vec<&OsStr>,
-        default_command_args: &Vec<&OsStr>,
+        config_args: &[&OsStr],
+        config_env: &[(String, String)],
+        default_command_args: &[&OsStr],
         match_args: &ArgMatches,
+        target: ProjectTarget,
         command_output: Option<CommandOutput>,
     ) -> Result<Output>;
+    /// Returns the full command line (binary and args) a compose command would run
+    fn command_line(
+        &self,
+        command_type: CommandType,
+        config_args: &[&OsStr],
+        default_command_args: &[&OsStr],
+        match_args: &ArgMatches,
+        target: ProjectTarget,
+    ) -> Result<Vec<OsString>>;
 }
 
 #[async_trait]
-impl Container for Docker {
-    fn init(bin_path: String) -> Self
-    where
-        Self: Sized,
-    {
-        Docker { bin_path }
+impl Container for ComposeEngine {
+    fn engine(&self) -> Engine {
+        self.engine
     }
 
     async fn compose(
         &self,
         command: CommandType,
-        config_args: &Vec<&OsStr>,
-        default_command_args: &Vec<&OsStr>,
+        config_args: &[&OsStr],
+        config_env: &[(String, String)],
+        default_command_args: &[&OsStr],
         match_args: &ArgMatches,
+        target: ProjectTarget,
         command_output: Option<CommandOutput>,
     ) -> Result<Output> {
         let output = if let Some(output) = command_output {
@@ -137,52 +155,101 @@ impl Container for Docker {
             CommandOutput::Status
         };
 
-        let cmd_args =
-            Self::prepare_command(self, command, config_args, default_command_args, match_args)?;
+        let cmd_args = self.prepare_command(
+            command,
+            config_args,
+            default_command_args,
+            match_args,
+            target,
+        )?;
 
-        let cmd_output = System::execute(self.bin_path.to_owned(), &cmd_args, &output).await?;
+        let cmd_output =
+            System::execute(self.bin_path.to_owned(), &cmd_args, config_env, &output).await?;
 
         Ok(cmd_output)
     }
+
+    fn command_line(
+        &self,
+        command: CommandType,
+        config_args: &[&OsStr],
+        default_command_args: &[&OsStr],
+        match_args: &ArgMatches,
+        target: ProjectTarget,
+    ) -> Result<Vec<OsString>> {
+        let mut command_line = vec![OsString::from(&self.bin_path)];
+        command_line.append(&mut self.prepare_command(
+            command,
+            config_args,
+            default_command_args,
+            match_args,
+            target,
+        )?);
+
+        Ok(command_line)
+    }
 }
 
-impl Docker {
+impl ComposeEngine {
     fn prepare_command(
         &self,
         command_type: CommandType,
-        config_args: &Vec<&OsStr>,
-        default_command_args: &Vec<&OsStr>,
+        config_args: &[&OsStr],
+        default_command_args: &[&OsStr],
         match_args: &ArgMatches,
+        target: ProjectTarget,
     ) -> Result<Vec<OsString>> {
-        // Get the command handler from the registry
-        let handler = get_command_by_name(command_type.as_str())
-            .ok_or_else(|| anyhow!("Unknown command: {}", command_type.as_str()))?;
-
-        // Build command arguments from matches args & mix with dctl_args
-        let mut args = handler.prepare(match_args);
-
-        // Build the full docker compose command
-        let mut docker_command_arg: Vec<OsString> = vec![OsString::from("compose")];
-        let mut only_args = args.split_off(1); // Remove first arg (command name)
+        let args = prepare_compose_args(
+            self.engine.compose_args(),
+            command_type,
+            config_args,
+            default_command_args,
+            match_args,
+            target,
+        )?;
+        self.engine.check_supported(command_type, &args)?;
+
+        Ok(args)
+    }
+}
 
-        // Add config args (like -f docker-compose.yml)
-        for arg in config_args {
-            docker_command_arg.push(OsString::from(arg));
-        }
+/// Args of a compose command: the args of the engine (like `compose`), the config args,
+/// the command name, the default args unless given on the command line, then the args
+pub fn prepare_compose_args(
+    engine_args: &[&str],
+    command_type: CommandType,
+    config_args: &[&OsStr],
+    default_command_args: &[&OsStr],
+    match_args: &ArgMatches,
+    target: ProjectTarget,
+) -> Result<Vec<OsString>> {
+    // Get the command handler from the registry
+    let handler = get_command_by_name(command_type.as_str())
+        .ok_or_else(|| anyhow!("Unknown command: {}", command_type.as_str()))?;
+
+    // Build command arguments from matches args & mix with dctl_args
+    let mut args = handler.prepare(match_args, target);
+
+    // Build the full compose command
+    let mut docker_command_arg: Vec<OsString> = engine_args.iter().map(OsString::from).collect();
+    let mut only_args = args.split_off(1); // Remove first arg (command name)
+
+    // Add config args (like -f docker-compose.yml)
+    for arg in config_args {
+        docker_command_arg.push(OsString::from(arg));
+    }
 
-        // Add command name
-        docker_command_arg.append(&mut args);
+    // Add command name
+    docker_command_arg.append(&mut args);
 
-        // Add default command args
-        for arg in default_command_args {
-            docker_command_arg.push(OsString::from(arg));
-        }
+    // Add default command args, unless given on the command line
+    let mut default_args = handler.merge_default_args(default_command_args, match_args);
+    docker_command_arg.append(&mut default_args);
 
-        // Add the rest of the args
-        docker_command_arg.append(&mut only_args);
+    // Add the rest of the args
+    docker_command_arg.append(&mut only_args);
 
-        Ok(docker_command_arg)
-    }
+    Ok(docker_command_arg)
 }
 
 #[cfg(test)]
@@ -191,10 +258,16 @@ mod tests {
     use std::ffi::OsStr;
     use crate::command::definitions::*;
 
+    fn get_docker(bin_path: &str) -> ComposeEngine {
+        ComposeEngine {
+            engine: Engine::Docker,
+            bin_path: bin_path.to_string(),
+        }
+    }
+
     #[test]
     fn it_prepares_docker_compose_down() {
-        let bin_path = "docker".to_string();
-        let docker = Docker::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -207,6 +280,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -221,8 +295,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_build() {
-        let bin_path = "docker".to_string();
-        let docker = Docker::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -235,6 +308,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -249,8 +323,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_create() {
-        let bin_path = "docker".to_string();
-        let docker = Docker::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -263,6 +336,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -277,8 +351,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_exec() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -291,6 +364,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -306,8 +380,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_events() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -320,6 +393,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -334,8 +408,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_kill() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -348,6 +421,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -362,8 +436,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_images() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -376,6 +449,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -390,8 +464,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_logs() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -404,6 +477,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -418,8 +492,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_ls() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -432,6 +505,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -446,8 +520,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_pause() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -460,6 +533,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -474,8 +548,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_port() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -488,6 +561,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -503,8 +577,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_ps() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -517,6 +590,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -531,8 +605,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_pull() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -545,6 +618,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -559,8 +633,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_push() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -573,6 +646,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -587,8 +661,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_restart() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -601,6 +674,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -615,8 +689,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_rm() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -629,6 +702,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -643,8 +717,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_run() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![OsStr::new("-i"), OsStr::new("--rm")];
@@ -657,6 +730,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -674,8 +748,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_start() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -688,6 +761,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -702,8 +776,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_stop() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -716,6 +789,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -730,8 +804,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_top() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -744,6 +817,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -758,8 +832,7 @@ mod tests {
 
     #[test]
     fn it_prepare_docker_compose_unpause() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -772,6 +845,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -786,8 +860,7 @@ mod tests {
 
     #[test]
     fn it_prepares_docker_compose_up() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![OsStr::new("-d")];
@@ -800,6 +873,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
@@ -813,10 +887,72 @@ mod tests {
         assert_eq!(cmd_args.unwrap(), expected_args);
     }
 
+    #[test]
+    fn it_prepares_docker_compose_up_with_overridden_default_args() {
+        let docker = get_docker("docker");
+
+        let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
+        let default_command_args =
+            vec![OsStr::new("-d"), OsStr::new("--timeout"), OsStr::new("10")];
+
+        let def = up_def();
+        let matches = def
+            .to_clap_command()
+            .get_matches_from(vec!["up", "--no-detach", "--timeout", "30", "PROJECT_NAME"]);
+
+        let cmd_args = docker.prepare_command(
+            CommandType::Up,
+            &config_args,
+            &default_command_args,
+            &matches,
+            ProjectTarget::default(),
+        );
+
+        let expected_args: Vec<OsString> = vec![
+            OsString::from("compose"),
+            OsString::from("-f"),
+            OsString::from("docker-compose.yml"),
+            OsString::from("up"),
+            OsString::from("--timeout"),
+            OsString::from("30"),
+        ];
+
+        assert_eq!(cmd_args.unwrap(), expected_args);
+    }
+
+    #[test]
+    fn it_returns_the_docker_compose_command_line() {
+        let docker = get_docker("/usr/bin/docker");
+
+        let config_args = vec![OsStr::new("-p"), OsStr::new("web")];
+        let default_command_args = vec![OsStr::new("-d")];
+
+        let def = up_def();
+        let matches = def.to_clap_command().get_matches_from(vec!["up", "PROJECT_NAME"]);
+
+        let command_line = docker.command_line(
+            CommandType::Up,
+            &config_args,
+            &default_command_args,
+            &matches,
+            ProjectTarget::default(),
+        );
+
+        let expected_args: Vec<OsString> = vec![
+            OsString::from("/usr/bin/docker"),
+            OsString::from("compose"),
+            OsString::from("-p"),
+            OsString::from("web"),
+            OsString::from("up"),
+            OsString::from("-d"),
+        ];
+
+        assert_eq!(command_line.unwrap(), expected_args);
+    }
+
     #[test]
     fn it_prepares_docker_compose_watch() {
-        let bin_path = "docker".to_string();
-        let docker: Docker = Container::init(bin_path.to_owned());
+        let docker = get_docker("docker");
 
         let config_args = vec![OsStr::new("-f"), OsStr::new("docker-compose.yml")];
         let default_command_args = vec![];
@@ -829,6 +965,7 @@ mod tests {
             &config_args,
             &default_command_args,
             &matches,
+            ProjectTarget::default(),
         );
 
         let expected_args: Vec<OsString> = vec![
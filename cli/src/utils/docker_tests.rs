@@ -1,15 +1,10 @@
 #[cfg(test)]
 mod tests {
-    use crate::utils::docker::{Container, Docker};
+    use crate::utils::engine::Engine;
 
     #[test]
-    fn it_initializes_with_bin_path() {
-        let docker: Docker = Container::init("../../tests/fake_docker".to_string());
-        assert!(
-            docker
-                == Docker {
-                    bin_path: "../../tests/fake_docker".to_string()
-                }
-        );
+    fn it_creates_the_container_of_an_engine() {
+        let container = Engine::Podman.container("../../tests/fake_docker".to_string());
+        assert_eq!(container.engine(), Engine::Podman);
     }
 }
@@ -0,0 +1,169 @@
+//! Dry-run output of the command lines dctl would execute
+
+use clap::ArgMatches;
+use serde_json::json;
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DryRunFormat {
+    Shell,
+    Json,
+}
+
+impl DryRunFormat {
+    pub fn from_name(name: &str) -> DryRunFormat {
+        match name {
+            "json" => DryRunFormat::Json,
+            _ => DryRunFormat::Shell,
+        }
+    }
+}
+
+/// Whether the global --dry-run option is set
+pub fn is_dry_run(args: &ArgMatches) -> bool {
+    args.try_get_one::<bool>("DRY_RUN").ok().flatten() == Some(&true)
+}
+
+/// Output format of the dry run, given with the global --dry-run-format option
+pub fn get_dry_run_format(args: &ArgMatches) -> DryRunFormat {
+    match args.try_get_one::<String>("DRY_RUN_FORMAT").ok().flatten() {
+        Some(name) => DryRunFormat::from_name(name),
+        None => DryRunFormat::Shell,
+    }
+}
+
+/// Format the command line of a project, as a shell command or a JSON object,
+/// with the environment variables the project adds
+pub fn format_command_line(
+    project: &str,
+    env: &[(String, String)],
+    command_line: &[OsString],
+    format: DryRunFormat,
+) -> String {
+    match format {
+        DryRunFormat::Shell => {
+            let mut line: Vec<String> = env
+                .iter()
+                .map(|(name, value)| shell_quote(&format!("{}={}", name, value)))
+                .collect();
+            line.push(shell_command_line(command_line));
+
+            line.join(" ")
+        }
+        DryRunFormat::Json => {
+            let args: Vec<String> = command_line
+                .iter()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect();
+
+            let mut output = json!({ "project": project, "command": args });
+            if !env.is_empty() {
+                output["env"] = json!(env.iter().cloned().collect::<BTreeMap<String, String>>());
+            }
+
+            output.to_string()
+        }
+    }
+}
+
+/// Format a command line as a shell command, quoting the arguments when needed
+pub fn shell_command_line(command_line: &[OsString]) -> String {
+    command_line
+        .iter()
+        .map(|arg| shell_quote(&arg.to_string_lossy()))
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+/// Quote an argument for a POSIX shell, only when needed
+fn shell_quote(arg: &str) -> String {
+    let is_safe = |c: char| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c);
+
+    if !arg.is_empty() && arg.chars().all(is_safe) {
+        arg.to_string()
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_command_line(args: &[&str]) -> Vec<OsString> {
+        args.iter().map(OsString::from).collect()
+    }
+
+    #[test]
+    fn it_returns_the_dry_run_format() {
+        assert_eq!(DryRunFormat::from_name("json"), DryRunFormat::Json);
+        assert_eq!(DryRunFormat::from_name("shell"), DryRunFormat::Shell);
+    }
+
+    #[test]
+    fn it_reads_the_dry_run_options() {
+        let cmd = clap::Command::new("dctl")
+            .arg(clap::Arg::new("DRY_RUN").long("dry-run").action(clap::ArgAction::SetTrue))
+            .arg(clap::Arg::new("DRY_RUN_FORMAT").long("dry-run-format"));
+
+        let matches = cmd.clone().get_matches_from(vec!["dctl", "--dry-run-format", "json"]);
+        assert!(!is_dry_run(&matches));
+        assert_eq!(get_dry_run_format(&matches), DryRunFormat::Json);
+
+        let matches = cmd.get_matches_from(vec!["dctl", "--dry-run"]);
+        assert!(is_dry_run(&matches));
+        assert_eq!(get_dry_run_format(&matches), DryRunFormat::Shell);
+
+        // Commands without the options are never run dry
+        let matches = clap::Command::new("dctl").get_matches_from(vec!["dctl"]);
+        assert!(!is_dry_run(&matches));
+    }
+
+    #[test]
+    fn it_quotes_shell_arguments() {
+        assert_eq!(shell_quote("docker-compose.yml"), "docker-compose.yml");
+        assert_eq!(shell_quote("/path/with space/.env"), "'/path/with space/.env'");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote(""), "''");
+    }
+
+    #[test]
+    fn it_formats_a_command_line_for_the_shell() {
+        let command_line = get_command_line(&["docker", "compose", "exec", "php", "echo $HOME"]);
+        assert_eq!(
+            format_command_line("web", &[], &command_line, DryRunFormat::Shell),
+            "docker compose exec php 'echo $HOME'"
+        );
+    }
+
+    #[test]
+    fn it_formats_the_environment_of_a_command_line() {
+        let command_line = get_command_line(&["docker", "compose", "ps"]);
+        let env = vec![
+            (String::from("DOCKER_HOST"), String::from("ssh://ci@build-vm")),
+            (String::from("GREETING"), String::from("hello world")),
+        ];
+        assert_eq!(
+            format_command_line("ci", &env, &command_line, DryRunFormat::Shell),
+            "DOCKER_HOST=ssh://ci@build-vm 'GREETING=hello world' docker compose ps"
+        );
+        assert_eq!(
+            format_command_line("ci", &env, &command_line, DryRunFormat::Json),
+            concat!(
+                r#"{"command":["docker","compose","ps"],"#,
+                r#""env":{"DOCKER_HOST":"ssh://ci@build-vm","GREETING":"hello world"},"#,
+                r#""project":"ci"}"#
+            )
+        );
+    }
+
+    #[test]
+    fn it_formats_a_command_line_as_json() {
+        let command_line = get_command_line(&["docker", "compose", "-p", "web", "up"]);
+        assert_eq!(
+            format_command_line("web", &[], &command_line, DryRunFormat::Json),
+            r#"{"command":["docker","compose","-p","web","up"],"project":"web"}"#
+        );
+    }
+}
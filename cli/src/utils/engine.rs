@@ -0,0 +1,329 @@
+//! Container engines running the compose commands, each spelling the compose
+//! invocation its own way
+
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+use std::ffi::OsString;
+
+use crate::parser::config::ComposeItem;
+use super::docker::{CommandType, ComposeEngine, Container};
+use super::error::DctlError;
+
+/// Engine running the compose commands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum Engine {
+    /// `docker compose`
+    #[default]
+    Docker,
+    /// `podman compose`
+    Podman,
+    /// `nerdctl compose`
+    Nerdctl,
+    /// The standalone `docker-compose` binary of Compose v2
+    DockerCompose,
+    /// The standalone `docker-compose` binary of Compose v1
+    DockerComposeV1,
+}
+
+impl Engine {
+    /// Binary of the engine, when the project engine is not the main one
+    pub fn default_bin(self) -> &'static str {
+        match self {
+            Engine::Docker => "docker",
+            Engine::Podman => "podman",
+            Engine::Nerdctl => "nerdctl",
+            Engine::DockerCompose | Engine::DockerComposeV1 => "docker-compose",
+        }
+    }
+
+    /// Compose invocation of the engine, as written in messages
+    pub fn invocation(self) -> &'static str {
+        match self {
+            Engine::Docker => "docker compose",
+            Engine::Podman => "podman compose",
+            Engine::Nerdctl => "nerdctl compose",
+            Engine::DockerCompose => "docker-compose",
+            Engine::DockerComposeV1 => "docker-compose v1",
+        }
+    }
+
+    /// Args of the binary before the compose command
+    pub fn compose_args(self) -> &'static [&'static str] {
+        match self {
+            Engine::Docker | Engine::Podman | Engine::Nerdctl => &["compose"],
+            Engine::DockerCompose | Engine::DockerComposeV1 => &[],
+        }
+    }
+
+    /// Variable naming the context of the daemon, podman having connections
+    pub fn context_var(self) -> &'static str {
+        match self {
+            Engine::Podman => "CONTAINER_CONNECTION",
+            _ => "DOCKER_CONTEXT",
+        }
+    }
+
+    /// Fail when the engine doesn't have the command, or one of its options, podman
+    /// compose being assumed to run docker-compose as its provider, with all its options
+    pub fn check_supported(self, command_type: CommandType, args: &[OsString]) -> Result<()> {
+        let (unsupported_commands, unsupported_options) = match self {
+            Engine::Nerdctl => (NERDCTL_UNSUPPORTED_COMMANDS, NERDCTL_UNSUPPORTED_OPTIONS),
+            Engine::DockerComposeV1 => {
+                (COMPOSE_V1_UNSUPPORTED_COMMANDS, COMPOSE_V1_UNSUPPORTED_OPTIONS)
+            }
+            Engine::Docker | Engine::Podman | Engine::DockerCompose => return Ok(()),
+        };
+
+        check_supported(self, command_type, args, unsupported_commands, unsupported_options)
+    }
+
+    pub fn container(self, bin_path: String) -> Box<dyn Container> {
+        Box::new(ComposeEngine {
+            engine: self,
+            bin_path,
+        })
+    }
+}
+
+/// Container of a project setting its own binary, or another engine than the main
+/// one, with the default binary of this engine
+pub fn get_project_container(
+    container: &dyn Container,
+    item: &ComposeItem,
+) -> Option<Box<dyn Container>> {
+    let engine = item.engine.unwrap_or(container.engine());
+    match &item.docker_bin {
+        Some(bin_path) => Some(engine.container(bin_path.to_string())),
+        None if engine != container.engine() => {
+            Some(engine.container(engine.default_bin().to_string()))
+        }
+        None => None,
+    }
+}
+
+/// Commands of Compose v2 that nerdctl compose doesn't have
+const NERDCTL_UNSUPPORTED_COMMANDS: &[CommandType] = &[
+    CommandType::Attach,
+    CommandType::Bridge,
+    CommandType::Commit,
+    CommandType::Events,
+    CommandType::Export,
+    CommandType::Ls,
+    CommandType::Publish,
+    CommandType::Scale,
+    CommandType::Stats,
+    CommandType::Volumes,
+    CommandType::Wait,
+    CommandType::Watch,
+];
+
+/// Options of docker compose that nerdctl compose doesn't have
+const NERDCTL_UNSUPPORTED_OPTIONS: &[(CommandType, &[&str])] = &[
+    (
+        CommandType::Build,
+        &[
+            "--builder",
+            "--memory",
+            "-m",
+            "--pull",
+            "--push",
+            "--quiet",
+            "-q",
+            "--ssh",
+            "--with-dependencies",
+        ],
+    ),
+    (CommandType::Down, &["--rmi", "--timeout", "-t"]),
+    (CommandType::Logs, &["--index", "--since", "--until"]),
+    (
+        CommandType::Up,
+        &[
+            "--always-recreate-deps",
+            "--attach-dependencies",
+            "--exit-code-from",
+            "--menu",
+            "--renew-anon-volumes",
+            "-V",
+            "--wait",
+            "--wait-timeout",
+            "--watch",
+            "-w",
+        ],
+    ),
+];
+
+/// Commands added by Compose v2
+const COMPOSE_V1_UNSUPPORTED_COMMANDS: &[CommandType] = &[
+    CommandType::Attach,
+    CommandType::Bridge,
+    CommandType::Commit,
+    CommandType::Cp,
+    CommandType::Export,
+    CommandType::Ls,
+    CommandType::Publish,
+    CommandType::Stats,
+    CommandType::Volumes,
+    CommandType::Wait,
+    CommandType::Watch,
+];
+
+/// Options of the commands added by Compose v2
+const COMPOSE_V1_UNSUPPORTED_OPTIONS: &[(CommandType, &[&str])] = &[
+    (CommandType::Build, &["--builder", "--push", "--ssh", "--with-dependencies"]),
+    (CommandType::Ps, &["--format", "--status"]),
+    (CommandType::Up, &["--menu", "--pull", "--wait", "--wait-timeout", "--watch"]),
+];
+
+/// Fail when the engine doesn't have the command, or one of its options
+fn check_supported(
+    engine: Engine,
+    command_type: CommandType,
+    args: &[OsString],
+    unsupported_commands: &[CommandType],
+    unsupported_options: &[(CommandType, &[&str])],
+) -> Result<()> {
+    if unsupported_commands.contains(&command_type) {
+        return Err(DctlError::Unsupported {
+            engine: engine.invocation().to_string(),
+            feature: format!("the {} command", command_type.as_str()),
+        }
+        .into());
+    }
+
+    let options = unsupported_options
+        .iter()
+        .filter(|(command, _)| *command == command_type)
+        .flat_map(|(_, options)| options.iter());
+    for option in options {
+        let given = args.iter().any(|arg| {
+            let arg = arg.to_string_lossy();
+            arg == *option || arg.starts_with(&format!("{}=", option))
+        });
+        if given {
+            return Err(DctlError::Unsupported {
+                engine: engine.invocation().to_string(),
+                feature: format!("the {} option of {}", option, command_type.as_str()),
+            }
+            .into());
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsStr;
+    use crate::command::args::ProjectTarget;
+    use crate::command::definitions::{ls_def, up_def};
+
+    fn get_up_command_line(engine: Engine, bin_path: &str, args: Vec<&str>) -> Result<String> {
+        let container = engine.container(bin_path.to_string());
+        let matches = up_def().to_clap_command().get_matches_from(args);
+        let config_args = vec![OsStr::new("-p"), OsStr::new("web")];
+
+        let default_args = vec![OsStr::new("-d")];
+        let target = ProjectTarget::default();
+        let command_line =
+            container.command_line(CommandType::Up, &config_args, &default_args, &matches, target)?;
+        let command_line: Vec<String> =
+            command_line.iter().map(|arg| arg.to_string_lossy().to_string()).collect();
+
+        Ok(command_line.join(" "))
+    }
+
+    fn get_ls_command_line(container: &dyn Container) -> Result<Vec<OsString>> {
+        let matches = ls_def().to_clap_command().get_matches_from(vec!["ls"]);
+
+        container.command_line(CommandType::Ls, &[], &[], &matches, ProjectTarget::default())
+    }
+
+    #[test]
+    fn it_spells_the_compose_invocation_of_each_engine() {
+        let command_line = |engine: Engine, bin_path: &str| {
+            get_up_command_line(engine, bin_path, vec!["up", "web"]).unwrap()
+        };
+
+        assert_eq!(command_line(Engine::Docker, "docker"), "docker compose -p web up -d");
+        assert_eq!(command_line(Engine::Podman, "podman"), "podman compose -p web up -d");
+        assert_eq!(command_line(Engine::Nerdctl, "nerdctl"), "nerdctl compose -p web up -d");
+        assert_eq!(command_line(Engine::DockerCompose, "compose"), "compose -p web up -d");
+        assert_eq!(command_line(Engine::DockerComposeV1, "compose"), "compose -p web up -d");
+    }
+
+    #[test]
+    fn it_refuses_the_commands_an_engine_does_not_have() {
+        let container = Engine::Nerdctl.container(String::from("nerdctl"));
+
+        let error = get_ls_command_line(container.as_ref()).unwrap_err();
+        assert_eq!(error.to_string(), "nerdctl compose does not support the ls command");
+    }
+
+    #[test]
+    fn it_refuses_the_options_an_engine_does_not_have() {
+        let args = vec!["up", "--wait", "web"];
+        let error =
+            get_up_command_line(Engine::DockerComposeV1, "compose", args.clone()).unwrap_err();
+        assert_eq!(error.to_string(), "docker-compose v1 does not support the --wait option of up");
+        assert!(get_up_command_line(Engine::DockerCompose, "compose", args.clone()).is_ok());
+
+        let error = get_up_command_line(Engine::Nerdctl, "nerdctl", args.clone()).unwrap_err();
+        assert_eq!(error.to_string(), "nerdctl compose does not support the --wait option of up");
+        assert!(get_up_command_line(Engine::Podman, "podman", args).is_ok());
+    }
+
+    #[test]
+    fn it_passes_every_command_and_option_to_podman() {
+        let options = NERDCTL_UNSUPPORTED_OPTIONS.iter().chain(COMPOSE_V1_UNSUPPORTED_OPTIONS);
+        for (command_type, options) in options {
+            let args: Vec<OsString> = options.iter().map(OsString::from).collect();
+            assert!(Engine::Podman.check_supported(*command_type, &args).is_ok());
+        }
+        let commands = NERDCTL_UNSUPPORTED_COMMANDS.iter().chain(COMPOSE_V1_UNSUPPORTED_COMMANDS);
+        for command_type in commands {
+            assert!(Engine::Podman.check_supported(*command_type, &[]).is_ok());
+        }
+    }
+
+    #[test]
+    fn it_returns_the_container_of_a_project_engine() {
+        let docker = Engine::Docker.container(String::from("/usr/bin/docker"));
+        let mut item = ComposeItem {
+            alias: String::from("web"),
+            ..Default::default()
+        };
+        assert!(get_project_container(docker.as_ref(), &item).is_none());
+
+        item.engine = Some(Engine::Docker);
+        assert!(get_project_container(docker.as_ref(), &item).is_none());
+
+        item.engine = Some(Engine::Podman);
+        let container = get_project_container(docker.as_ref(), &item).unwrap();
+        assert_eq!(container.engine(), Engine::Podman);
+        assert_eq!(get_ls_command_line(container.as_ref()).unwrap()[0], "podman");
+    }
+
+    #[test]
+    fn it_returns_the_container_of_a_project_binary() {
+        let docker = Engine::Docker.container(String::from("/usr/bin/docker"));
+        let mut item = ComposeItem {
+            alias: String::from("web"),
+            docker_bin: Some(String::from("/opt/docker/bin/docker")),
+            ..Default::default()
+        };
+
+        let container = get_project_container(docker.as_ref(), &item).unwrap();
+        assert_eq!(container.engine(), Engine::Docker);
+        let command_line = get_ls_command_line(container.as_ref()).unwrap();
+        assert_eq!(command_line[0], "/opt/docker/bin/docker");
+
+        item.engine = Some(Engine::DockerCompose);
+        let container = get_project_container(docker.as_ref(), &item).unwrap();
+        assert_eq!(container.engine(), Engine::DockerCompose);
+        let command_line = get_ls_command_line(container.as_ref()).unwrap();
+        assert_eq!(command_line[..2], ["/opt/docker/bin/docker", "ls"]);
+    }
+}
@@ -0,0 +1,219 @@
+//! Errors dctl reports to the user, each with its own exit code
+
+use std::fmt;
+
+use super::system::CommandError;
+
+/// The config file can't be read or is invalid (EX_CONFIG)
+pub const EXIT_CONFIG: i32 = 78;
+/// The project alias or group is unknown, or no project was given (EX_USAGE)
+pub const EXIT_UNKNOWN_PROJECT: i32 = 64;
+/// A file of a project does not exist (EX_NOINPUT)
+pub const EXIT_MISSING_FILE: i32 = 66;
+/// The engine of the project doesn't support the command (EX_UNAVAILABLE)
+pub const EXIT_UNSUPPORTED: i32 = 69;
+/// The docker binary can't be found, as a shell would do
+pub const EXIT_DOCKER_NOT_FOUND: i32 = 127;
+/// Any other error
+pub const EXIT_FAILURE: i32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DctlError {
+    /// The config file can't be read or parsed
+    Config(String),
+    /// No alias matches, with the closest aliases
+    UnknownAlias { alias: String, suggestions: Vec<String> },
+    /// The alias prefix matches several aliases
+    AmbiguousAlias { alias: String, candidates: Vec<String> },
+    UnknownGroup(String),
+    NoProject,
+    /// A compose or environment file does not exist
+    MissingFile { kind: String, path: String },
+    DockerNotFound(String),
+    /// The engine doesn't support a command or an option
+    Unsupported { engine: String, feature: String },
+    /// The command of a secret env variable failed, the reason never holds the secret
+    Secret { alias: String, name: String, reason: String },
+    /// The docker command exited with a failure status
+    CommandFailed(CommandError),
+    /// The docker command was stopped by a signal received by dctl
+    Aborted { signal: i32 },
+}
+
+impl DctlError {
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            DctlError::Config(_) => EXIT_CONFIG,
+            DctlError::UnknownAlias { .. }
+            | DctlError::AmbiguousAlias { .. }
+            | DctlError::UnknownGroup(_)
+            | DctlError::NoProject => EXIT_UNKNOWN_PROJECT,
+            DctlError::MissingFile { .. } => EXIT_MISSING_FILE,
+            DctlError::DockerNotFound(_) => EXIT_DOCKER_NOT_FOUND,
+            DctlError::Unsupported { .. } => EXIT_UNSUPPORTED,
+            DctlError::Secret { .. } => EXIT_FAILURE,
+            DctlError::CommandFailed(error) => error.exit_code(),
+            DctlError::Aborted { signal } => 128 + signal,
+        }
+    }
+
+    /// What the user can do to fix the error
+    pub fn hint(&self) -> Option<&str> {
+        match self {
+            DctlError::Config(_) => Some(
+                "check the config file, or set DCTL_CONFIG_FILE_PATH to load another one",
+            ),
+            DctlError::UnknownAlias { .. } => {
+                Some("run `dctl infos` to list aliases, or `dctl register` to add a project")
+            }
+            DctlError::AmbiguousAlias { .. } => Some("type more characters of the alias"),
+            DctlError::UnknownGroup(_) => {
+                Some("add the group to the `groups` of a project in the config file")
+            }
+            DctlError::NoProject => Some(
+                "give a project alias, `--group <GROUP>` or `--all`, or run it in a project directory",
+            ),
+            DctlError::MissingFile { .. } => {
+                Some("fix the path, `dctl check-config` lists the missing files of all projects")
+            }
+            DctlError::DockerNotFound(_) => {
+                Some("install docker, or set `docker_bin` in the [main] section of the config file")
+            }
+            DctlError::Unsupported { .. } => {
+                Some("set another `engine` for the project, in its section of the config file")
+            }
+            DctlError::Secret { .. } => Some(
+                "check the `command` of the variable, in the `env` table of the project",
+            ),
+            DctlError::CommandFailed(_) | DctlError::Aborted { .. } => None,
+        }
+    }
+}
+
+impl fmt::Display for DctlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DctlError::Config(message) => write!(f, "{}", message),
+            DctlError::UnknownAlias { alias, suggestions } => {
+                write!(f, "No project found with alias: {}", alias)?;
+                if !suggestions.is_empty() {
+                    write!(f, ", did you mean: {}?", suggestions.join(", "))?;
+                }
+                Ok(())
+            }
+            DctlError::AmbiguousAlias { alias, candidates } => {
+                write!(f, "Alias {} matches several projects: {}", alias, candidates.join(", "))
+            }
+            DctlError::UnknownGroup(group) => write!(f, "No project found in group: {}", group),
+            DctlError::NoProject => write!(f, "No project provided"),
+            DctlError::MissingFile { kind, path } => write!(f, "{} does not exist: {}", kind, path),
+            DctlError::DockerNotFound(bin) => write!(f, "Docker binary not found: {}", bin),
+            DctlError::Unsupported { engine, feature } => {
+                write!(f, "{} does not support {}", engine, feature)
+            }
+            DctlError::Secret { alias, name, reason } => {
+                write!(f, "Failed to read the secret {} of project {}: {}", name, alias, reason)
+            }
+            DctlError::CommandFailed(error) => write!(f, "{}", error),
+            DctlError::Aborted { signal } => write!(f, "Interrupted by signal {}", signal),
+        }
+    }
+}
+
+impl std::error::Error for DctlError {}
+
+impl From<CommandError> for DctlError {
+    fn from(error: CommandError) -> Self {
+        DctlError::CommandFailed(error)
+    }
+}
+
+/// Print an error returned by dctl with its hint, and return the exit code to use.
+/// A failed docker command already printed its own errors, unless its output was captured.
+pub fn report_error(error: &anyhow::Error) -> i32 {
+    let Some(dctl_error) = error.downcast_ref::<DctlError>() else {
+        eprintln!("{}", error);
+        return EXIT_FAILURE;
+    };
+
+    match dctl_error {
+        DctlError::CommandFailed(command_error) if command_error.stderr.is_empty() => {}
+        DctlError::Aborted { .. } => {}
+        _ => eprintln!("{}", error),
+    }
+    if let Some(hint) = dctl_error.hint() {
+        eprintln!("hint: {}", hint);
+    }
+
+    dctl_error.exit_code()
+}
+
+#[cfg(test)]
+mod tests {
+    use anyhow::anyhow;
+
+    use super::*;
+
+    fn get_unknown_alias_error(suggestions: Vec<String>) -> DctlError {
+        DctlError::UnknownAlias {
+            alias: String::from("wbe"),
+            suggestions,
+        }
+    }
+
+    #[test]
+    fn it_returns_the_exit_code_of_each_error() {
+        assert_eq!(DctlError::Config(String::from("invalid")).exit_code(), 78);
+        assert_eq!(get_unknown_alias_error(vec![]).exit_code(), 64);
+        assert_eq!(DctlError::UnknownGroup(String::from("front")).exit_code(), 64);
+        assert_eq!(DctlError::NoProject.exit_code(), 64);
+        assert_eq!(
+            DctlError::MissingFile {
+                kind: String::from("Compose file"),
+                path: String::from("/tmp/docker-compose.yml"),
+            }
+            .exit_code(),
+            66
+        );
+        assert_eq!(DctlError::DockerNotFound(String::from("docker")).exit_code(), 127);
+        assert_eq!(
+            DctlError::Unsupported {
+                engine: String::from("nerdctl compose"),
+                feature: String::from("the ls command"),
+            }
+            .exit_code(),
+            69
+        );
+        assert_eq!(DctlError::Aborted { signal: 2 }.exit_code(), 130);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_returns_the_exit_code_of_a_failed_command() {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::ExitStatus;
+
+        let error = DctlError::from(CommandError::new(ExitStatus::from_raw(4 << 8), vec![]));
+        assert_eq!(error.exit_code(), 4);
+        assert_eq!(error.hint(), None);
+    }
+
+    #[test]
+    fn it_gives_a_hint_for_an_unknown_alias() {
+        let error = get_unknown_alias_error(vec![]);
+        assert_eq!(error.to_string(), "No project found with alias: wbe");
+        assert!(error.hint().unwrap().contains("dctl infos"));
+    }
+
+    #[test]
+    fn it_suggests_the_closest_aliases() {
+        let error = get_unknown_alias_error(vec![String::from("web"), String::from("wab")]);
+        assert_eq!(error.to_string(), "No project found with alias: wbe, did you mean: web, wab?");
+    }
+
+    #[test]
+    fn it_reports_the_exit_code_of_any_error() {
+        assert_eq!(report_error(&anyhow!("failed")), 1);
+        assert_eq!(report_error(&DctlError::NoProject.into()), 64);
+    }
+}
@@ -0,0 +1,162 @@
+//! Environment variables of the projects, reading their secrets from shell commands
+
+use anyhow::Result;
+use std::process::Stdio;
+use tokio::process::Command;
+
+use crate::parser::config::EnvValue;
+use super::error::DctlError;
+
+/// Printed in place of a secret value
+pub const MASKED_SECRET: &str = "***";
+
+/// Env of a project, each secret value replaced by its mask, to be printed
+pub fn mask_env(env: &[(String, EnvValue)]) -> Vec<(String, String)> {
+    env.iter()
+        .map(|(name, value)| match value {
+            EnvValue::Value(value) => (name.to_string(), value.to_string()),
+            EnvValue::Secret { .. } => (name.to_string(), MASKED_SECRET.to_string()),
+        })
+        .collect()
+}
+
+/// Env of a project without its secrets, for the commands which don't use them,
+/// like the status checks of `dctl infos`
+pub fn skip_secrets(env: &[(String, EnvValue)]) -> Vec<(String, String)> {
+    env.iter()
+        .filter_map(|(name, value)| match value {
+            EnvValue::Value(value) => Some((name.to_string(), value.to_string())),
+            EnvValue::Secret { .. } => None,
+        })
+        .collect()
+}
+
+/// Env of a project, running the command of each secret
+pub async fn resolve_env(
+    alias: &str,
+    env: &[(String, EnvValue)],
+) -> Result<Vec<(String, String)>> {
+    let mut resolved = Vec::new();
+    for (name, value) in env {
+        let value = match value {
+            EnvValue::Value(value) => value.to_string(),
+            EnvValue::Secret { command } => {
+                read_secret(command).await.map_err(|reason| DctlError::Secret {
+                    alias: alias.to_string(),
+                    name: name.to_string(),
+                    reason,
+                })?
+            }
+        };
+        resolved.push((name.to_string(), value));
+    }
+
+    Ok(resolved)
+}
+
+/// Run the command of a secret and return its output, without the trailing newline.
+/// The error never holds the output, the command may have printed the secret.
+async fn read_secret(command: &str) -> Result<String, String> {
+    #[cfg(unix)]
+    let mut cmd = Command::new("sh");
+    #[cfg(unix)]
+    cmd.arg("-c");
+    #[cfg(windows)]
+    let mut cmd = Command::new("cmd");
+    #[cfg(windows)]
+    cmd.arg("/C");
+
+    // The command may prompt for a passphrase, only its output is captured, `output()`
+    // would capture its error output too
+    let output = cmd
+        .arg(command)
+        .stdin(Stdio::inherit())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::inherit())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|err| format!("`{}` can't be run: {}", command, err))?
+        .wait_with_output()
+        .await
+        .map_err(|err| format!("`{}` can't be run: {}", command, err))?;
+
+    if !output.status.success() {
+        return Err(match output.status.code() {
+            Some(code) => format!("`{}` failed with exit code {}", command, code),
+            None => format!("`{}` failed without exit code", command),
+        });
+    }
+
+    let output = String::from_utf8(output.stdout)
+        .map_err(|_| format!("the output of `{}` is not valid UTF-8", command))?;
+    let secret = output.strip_suffix("\r\n").or_else(|| output.strip_suffix('\n'));
+
+    Ok(secret.unwrap_or(&output).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn get_env() -> Vec<(String, EnvValue)> {
+        vec![
+            (String::from("DB_USER"), EnvValue::Value(String::from("shop"))),
+            (
+                String::from("DB_PASSWORD"),
+                EnvValue::Secret {
+                    command: String::from("printf 's3cret\\n'"),
+                },
+            ),
+        ]
+    }
+
+    #[test]
+    fn it_masks_the_secret_values() {
+        assert_eq!(
+            mask_env(&get_env()),
+            vec![
+                (String::from("DB_USER"), String::from("shop")),
+                (String::from("DB_PASSWORD"), String::from("***")),
+            ]
+        );
+    }
+
+    #[test]
+    fn it_skips_the_secret_values() {
+        assert_eq!(
+            skip_secrets(&get_env()),
+            vec![(String::from("DB_USER"), String::from("shop"))]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_reads_the_secret_values() {
+        assert_eq!(
+            resolve_env("shop", &get_env()).await.unwrap(),
+            vec![
+                (String::from("DB_USER"), String::from("shop")),
+                (String::from("DB_PASSWORD"), String::from("s3cret")),
+            ]
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_does_not_print_the_output_of_a_failed_secret_command() {
+        let env = vec![(
+            String::from("DB_PASSWORD"),
+            EnvValue::Secret {
+                command: String::from("echo s3cret; exit 3"),
+            },
+        )];
+
+        let error = resolve_env("shop", &env).await.unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "Failed to read the secret DB_PASSWORD of project shop: \
+                `echo s3cret; exit 3` failed with exit code 3"
+        );
+        assert_eq!(error.downcast_ref::<DctlError>().unwrap().exit_code(), 1);
+    }
+}
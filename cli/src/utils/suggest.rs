@@ -0,0 +1,64 @@
+//! Suggestions of known names close to a mistyped one
+
+/// Maximum number of suggestions returned
+const MAX_SUGGESTIONS: usize = 3;
+
+/// Levenshtein distance between two strings
+pub fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1; b.len() + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        previous = current;
+    }
+
+    previous[b.len()]
+}
+
+/// Candidates close enough to the name, the closest first
+pub fn closest_names<'a, I>(name: &str, candidates: I) -> Vec<String>
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    let max_distance = (name.chars().count() / 3).max(2);
+
+    let mut matches: Vec<(usize, &str)> = candidates
+        .into_iter()
+        .map(|candidate| (edit_distance(name, candidate), candidate))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .collect();
+    matches.sort();
+
+    matches
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate.to_string())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn it_computes_the_edit_distance() {
+        assert_eq!(edit_distance("web", "web"), 0);
+        assert_eq!(edit_distance("wbe", "web"), 2);
+        assert_eq!(edit_distance("we", "web"), 1);
+        assert_eq!(edit_distance("", "web"), 3);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn it_returns_the_closest_names_first() {
+        let candidates = vec!["web", "data", "stack_monitoring", "wab"];
+        assert_eq!(closest_names("wbe", candidates.clone()), vec!["wab", "web"]);
+        assert_eq!(closest_names("stack_monitorng", candidates.clone()), vec!["stack_monitoring"]);
+        assert!(closest_names("unknown", candidates).is_empty());
+    }
+}
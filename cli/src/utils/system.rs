@@ -1,8 +1,10 @@
-use anyhow::{anyhow, Result};
+use anyhow::{Context, Result};
 use mockall::automock;
 use std::ffi::OsString;
-use std::process::Output;
-use tokio::process::Command;
+use std::fmt;
+use std::process::{ExitStatus, Output, Stdio};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::{Child, Command};
 
 #[cfg(unix)]
 use std::os::unix::process::ExitStatusExt;
@@ -10,10 +12,114 @@ use std::os::unix::process::ExitStatusExt;
 use std::os::windows::process::ExitStatusExt;
 
 use super::docker::CommandOutput;
+use super::dry_run::shell_command_line;
+use super::error::DctlError;
 
 #[derive(PartialEq, Eq)]
 pub struct System {}
 
+/// Error of a command exiting with a failure status
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommandError {
+    pub status: ExitStatus,
+    /// Program and arguments of the failed command
+    pub command_line: Vec<OsString>,
+    /// Trimmed error output, only when the output was captured
+    pub stderr: String,
+}
+
+impl CommandError {
+    pub fn new(status: ExitStatus, command_line: Vec<OsString>) -> CommandError {
+        CommandError {
+            status,
+            command_line,
+            stderr: String::new(),
+        }
+    }
+
+    pub fn with_stderr(mut self, stderr: &[u8]) -> CommandError {
+        self.stderr = String::from_utf8_lossy(stderr).trim().to_string();
+        self
+    }
+
+    /// Exit code to propagate: the command exit code, or 128 + signal
+    /// when the command was killed by a signal
+    pub fn exit_code(&self) -> i32 {
+        if let Some(code) = self.status.code() {
+            return code;
+        }
+        #[cfg(unix)]
+        if let Some(signal) = self.status.signal() {
+            return 128 + signal;
+        }
+
+        1
+    }
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "`{}` failed", shell_command_line(&self.command_line))?;
+
+        match self.status.code() {
+            Some(code) => write!(f, " with exit code {}", code)?,
+            #[cfg(unix)]
+            None if self.status.signal().is_some() => {
+                write!(f, ", killed by signal {}", self.status.signal().unwrap_or(0))?
+            }
+            None => write!(f, " without exit code")?,
+        }
+        if !self.stderr.is_empty() {
+            write!(f, ": {}", self.stderr)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl std::error::Error for CommandError {}
+
+/// What to do with a signal received by dctl while a child runs
+#[cfg(unix)]
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum SignalAction {
+    Forward,
+    Kill,
+    Ignore,
+}
+
+/// Forward the received signal to the child, the `received` count including it, up
+/// to the second one (docker compose then forces the stop), kill the child on the
+/// third one. A Ctrl-C in the terminal is already sent to the child, as it runs
+/// in the same foreground process group.
+#[cfg(unix)]
+pub(crate) fn signal_action(
+    signal: i32,
+    received: usize,
+    terminal_foreground: bool,
+) -> SignalAction {
+    if received > 2 {
+        SignalAction::Kill
+    } else if signal == libc::SIGINT && terminal_foreground {
+        SignalAction::Ignore
+    } else {
+        SignalAction::Forward
+    }
+}
+
+/// Whether dctl runs in the foreground process group of its controlling terminal,
+/// whatever its standard input is
+#[cfg(unix)]
+fn is_terminal_foreground() -> bool {
+    use std::os::fd::AsRawFd;
+
+    let Ok(tty) = std::fs::File::open("/dev/tty") else {
+        return false;
+    };
+    // SAFETY: both calls only read the process state, the fd is open until the end
+    unsafe { libc::tcgetpgrp(tty.as_raw_fd()) == libc::getpgrp() }
+}
+
 #[automock]
 impl System {
     pub fn builder(bin_command: String, sorted_args: Vec<OsString>) -> Command {
@@ -30,40 +136,164 @@ impl System {
     pub async fn execute(
         bin_command_path: String,
         command_arg: &[OsString],
+        env: &[(String, String)],
         output: &CommandOutput,
     ) -> Result<Output> {
-        // Build command
-        let mut cmd: Command = System::builder(bin_command_path, command_arg.to_vec());
+        let command_line: Vec<OsString> = std::iter::once(OsString::from(&bin_command_path))
+            .chain(command_arg.iter().cloned())
+            .collect();
+
+        // Build command, the child must not outlive dctl
+        let mut cmd: Command = System::builder(bin_command_path.clone(), command_arg.to_vec());
+        cmd.envs(env.iter().cloned());
+        cmd.kill_on_drop(true);
+        #[cfg(target_os = "linux")]
+        // SAFETY: prctl is async-signal-safe, it's the only call made between fork and exec
+        unsafe {
+            cmd.pre_exec(|| {
+                libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGTERM);
+                Ok(())
+            });
+        }
 
         // Execute command asynchronously using tokio::process::Command
         match output {
             CommandOutput::Status => {
-                let status = cmd.status().await?;
-
-                if status.success() {
-                    #[cfg(unix)]
-                    let exit_status = std::process::ExitStatus::from_raw(status.code().unwrap_or(0));
-                    #[cfg(windows)]
-                    let exit_status = std::process::ExitStatus::from_raw(status.code().unwrap_or(0) as u32);
-
-                    Ok(Output {
-                        status: exit_status,
-                        stdout: vec![],
-                        stderr: vec![],
-                    })
-                } else {
-                    Err(anyhow!("Command failed with status: {:?}", status.code()))
-                }
+                let mut child = System::spawn(&mut cmd, &bin_command_path)?;
+                let (status, signal) = System::wait_forwarding_signals(&mut child).await?;
+
+                System::status_output(status, signal, command_line)
             }
             CommandOutput::Output => {
-                let output = cmd.output().await?;
+                let output = cmd
+                    .output()
+                    .await
+                    .map_err(|err| System::spawn_error(err, &bin_command_path))?;
 
                 if output.status.success() {
                     Ok(output)
                 } else {
-                    Err(anyhow!("Command failed with status: {:?}", output.status.code()))
+                    let error = CommandError::new(output.status, command_line);
+                    Err(DctlError::from(error.with_stderr(&output.stderr)).into())
                 }
             }
+            CommandOutput::Stream(prefix) => {
+                cmd.stdin(Stdio::null()).stdout(Stdio::piped()).stderr(Stdio::piped());
+                let mut child = System::spawn(&mut cmd, &bin_command_path)?;
+                let stdout = child.stdout.take().context("Failed to capture stdout")?;
+                let stderr = child.stderr.take().context("Failed to capture stderr")?;
+
+                let (result, _, _) = tokio::join!(
+                    System::wait_forwarding_signals(&mut child),
+                    System::print_lines(stdout, prefix, false),
+                    System::print_lines(stderr, prefix, true),
+                );
+
+                let (status, signal) = result?;
+
+                System::status_output(status, signal, command_line)
+            }
+        }
+    }
+
+    fn spawn(cmd: &mut Command, bin_command_path: &str) -> Result<Child> {
+        cmd.spawn().map_err(|err| System::spawn_error(err, bin_command_path))
+    }
+
+    fn spawn_error(err: std::io::Error, bin_command_path: &str) -> anyhow::Error {
+        if err.kind() == std::io::ErrorKind::NotFound {
+            DctlError::DockerNotFound(bin_command_path.to_string()).into()
+        } else {
+            err.into()
+        }
+    }
+
+    /// Wait for a child process, forwarding the SIGINT, SIGTERM and SIGHUP received
+    /// by dctl as told by `signal_action`. Returns the last signal received, if any.
+    #[cfg(unix)]
+    async fn wait_forwarding_signals(child: &mut Child) -> Result<(ExitStatus, Option<i32>)> {
+        use tokio::signal::unix::{signal, SignalKind};
+
+        let mut sigint = signal(SignalKind::interrupt())?;
+        let mut sigterm = signal(SignalKind::terminate())?;
+        let mut sighup = signal(SignalKind::hangup())?;
+        let mut received = 0;
+        let mut last_signal = None;
+
+        loop {
+            let signal = tokio::select! {
+                status = child.wait() => return Ok((status?, last_signal)),
+                _ = sigint.recv() => libc::SIGINT,
+                _ = sigterm.recv() => libc::SIGTERM,
+                _ = sighup.recv() => libc::SIGHUP,
+            };
+            received += 1;
+            last_signal = Some(signal);
+
+            let Some(pid) = child.id() else {
+                continue;
+            };
+            match signal_action(signal, received, is_terminal_foreground()) {
+                SignalAction::Forward => {
+                    // SAFETY: kill has no memory safety requirement, pid is our own child
+                    unsafe {
+                        libc::kill(pid as libc::pid_t, signal);
+                    }
+                }
+                SignalAction::Kill => child.start_kill()?,
+                SignalAction::Ignore => {}
+            }
+        }
+    }
+
+    #[cfg(windows)]
+    async fn wait_forwarding_signals(child: &mut Child) -> Result<(ExitStatus, Option<i32>)> {
+        // Ctrl-C is sent to every process of the console, wait for the child to stop
+        let mut last_signal = None;
+        loop {
+            tokio::select! {
+                status = child.wait() => return Ok((status?, last_signal)),
+                _ = tokio::signal::ctrl_c() => last_signal = Some(2),
+            }
+        }
+    }
+
+    fn status_output(
+        status: ExitStatus,
+        signal: Option<i32>,
+        command_line: Vec<OsString>,
+    ) -> Result<Output> {
+        if status.success() {
+            #[cfg(unix)]
+            let exit_status = std::process::ExitStatus::from_raw(status.code().unwrap_or(0));
+            #[cfg(windows)]
+            let exit_status = std::process::ExitStatus::from_raw(status.code().unwrap_or(0) as u32);
+
+            Ok(Output {
+                status: exit_status,
+                stdout: vec![],
+                stderr: vec![],
+            })
+        } else {
+            match signal {
+                Some(signal) => Err(DctlError::Aborted { signal }.into()),
+                None => Err(DctlError::from(CommandError::new(status, command_line)).into()),
+            }
+        }
+    }
+
+    /// Print each line read from a child output, prefixed with the given tag
+    async fn print_lines<R: AsyncRead + Unpin + 'static>(reader: R, prefix: &str, to_stderr: bool) {
+        let mut lines = BufReader::new(reader).split(b'\n');
+
+        while let Ok(Some(line)) = lines.next_segment().await {
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches('\r');
+            if to_stderr {
+                eprintln!("{} {}", prefix, line);
+            } else {
+                println!("{} {}", prefix, line);
+            }
         }
     }
 }
@@ -2,7 +2,9 @@
 mod tests {
     use std::ffi::OsString;
 
-    use crate::utils::system::System;
+    use crate::utils::docker::CommandOutput;
+    use crate::utils::error::DctlError;
+    use crate::utils::system::{CommandError, System};
 
     #[test]
     fn it_builds_a_system_command_process() {
@@ -17,4 +19,114 @@ mod tests {
         assert_eq!(std_cmd.get_program(), std::ffi::OsStr::new(&bin_command));
         assert_eq!(cmd_args, vec![std::ffi::OsStr::new("-l"), std::ffi::OsStr::new("-a")]);
     }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_streams_a_successful_command_output() {
+        let args = vec![OsString::from("-c"), OsString::from("echo out; echo err >&2")];
+        let output = CommandOutput::Stream("[test]".to_string());
+
+        let result = System::execute("sh".to_string(), &args, &[], &output).await;
+        assert!(result.unwrap().status.success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_streams_a_failing_command_output() {
+        let args = vec![OsString::from("-c"), OsString::from("echo out; exit 3")];
+        let output = CommandOutput::Stream("[test]".to_string());
+
+        let result = System::execute("sh".to_string(), &args, &[], &output).await;
+        assert!(result.unwrap_err().to_string().contains("exit code 3"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_returns_the_exit_code_of_a_failed_command() {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::ExitStatus;
+
+        let error = CommandError::new(ExitStatus::from_raw(2 << 8), vec![]);
+        assert_eq!(error.exit_code(), 2);
+
+        // Killed by SIGKILL
+        let error = CommandError::new(ExitStatus::from_raw(9), vec![]);
+        assert_eq!(error.exit_code(), 137);
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_returns_a_command_error_on_failure() {
+        let args = vec![OsString::from("-c"), OsString::from("exit 2")];
+
+        let result = System::execute("sh".to_string(), &args, &[], &CommandOutput::Status).await;
+        let error = result.unwrap_err();
+        assert_eq!(error.downcast_ref::<DctlError>().unwrap().exit_code(), 2);
+    }
+
+    #[tokio::test]
+    async fn it_returns_an_error_when_the_binary_is_not_found() {
+        let bin_path = "/not/found/docker".to_string();
+        let result = System::execute(bin_path, &[], &[], &CommandOutput::Status).await;
+        let error = result.unwrap_err();
+        assert_eq!(
+            error.downcast_ref::<DctlError>(),
+            Some(&DctlError::DockerNotFound("/not/found/docker".to_string()))
+        );
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_sets_the_environment_of_a_command() {
+        let args = vec![OsString::from("-c"), OsString::from("test \"$DOCKER_HOST\" = ssh://vm")];
+        let env = vec![(String::from("DOCKER_HOST"), String::from("ssh://vm"))];
+
+        let result = System::execute("sh".to_string(), &args, &env, &CommandOutput::Output).await;
+        assert!(result.unwrap().status.success());
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn it_keeps_the_error_output_of_a_captured_command() {
+        let args = vec![OsString::from("-c"), OsString::from("echo no such service >&2; exit 1")];
+
+        let result = System::execute("sh".to_string(), &args, &[], &CommandOutput::Output).await;
+        let error = result.unwrap_err();
+        let Some(DctlError::CommandFailed(command_error)) = error.downcast_ref::<DctlError>() else {
+            panic!("Expected a failed command error");
+        };
+
+        assert_eq!(command_error.exit_code(), 1);
+        assert_eq!(command_error.command_line[0], OsString::from("sh"));
+        assert_eq!(command_error.stderr, "no such service");
+        assert!(error.to_string().starts_with("`sh -c "));
+        assert!(error.to_string().ends_with("failed with exit code 1: no such service"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_describes_a_command_killed_by_a_signal() {
+        use std::os::unix::process::ExitStatusExt;
+        use std::process::ExitStatus;
+
+        let command_line = vec![OsString::from("docker"), OsString::from("compose")];
+        let error = CommandError::new(ExitStatus::from_raw(15), command_line);
+        assert_eq!(error.to_string(), "`docker compose` failed, killed by signal 15");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn it_forwards_the_signals_not_sent_by_the_terminal() {
+        use crate::utils::system::{signal_action, SignalAction};
+
+        // A Ctrl-C in the foreground terminal already reached the child
+        assert_eq!(signal_action(libc::SIGINT, 1, true), SignalAction::Ignore);
+        assert_eq!(signal_action(libc::SIGINT, 1, false), SignalAction::Forward);
+        assert_eq!(signal_action(libc::SIGTERM, 1, true), SignalAction::Forward);
+        assert_eq!(signal_action(libc::SIGHUP, 2, false), SignalAction::Forward);
+
+        // The third signal kills the child
+        assert_eq!(signal_action(libc::SIGINT, 3, true), SignalAction::Kill);
+        assert_eq!(signal_action(libc::SIGTERM, 3, false), SignalAction::Kill);
+    }
 }